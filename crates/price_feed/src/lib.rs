@@ -71,6 +71,6 @@ mod tests {
             get_usd_price_for_assets(None, vec!["weth".to_string(), "usd-coin".to_string()])
                 .await
                 .unwrap();
-        assert!(prices.len() > 0);
+        assert!(!prices.is_empty());
     }
 }
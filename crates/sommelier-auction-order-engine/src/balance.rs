@@ -0,0 +1,150 @@
+use sommelier_auction::{amount::TokenAmount, bid::Bid};
+
+use crate::events::LowBalance;
+
+/// What the wallet has to pay for a bid with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletBalance {
+    /// The wallet's uSOMM balance
+    pub balance: u128,
+    /// uSOMM of bids that were sent but may not have landed yet. Bids that landed have already
+    /// been paid out of the balance.
+    pub outstanding: u128,
+    /// The fee of the bid's transaction
+    pub fee: u128,
+}
+
+impl WalletBalance {
+    /// The uSOMM a bid can spend
+    pub fn available(&self) -> u128 {
+        self.balance
+            .saturating_sub(self.outstanding)
+            .saturating_sub(self.fee)
+    }
+
+    /// The bid to submit: `bid` itself if the wallet can cover it, cut down to what's available
+    /// if `shrink` is set, or why it can't be submitted.
+    pub fn fit(&self, bid: &Bid, shrink: bool) -> std::result::Result<Bid, String> {
+        let available = self.available();
+        if bid.maximum_usomm_in.raw <= available {
+            return Ok(bid.clone());
+        }
+
+        let reason = format!(
+            "bid of {} exceeds the {} the wallet has available after {} held for bids that may still land and a {} fee",
+            bid.maximum_usomm_in,
+            TokenAmount::usomm(available),
+            TokenAmount::usomm(self.outstanding),
+            TokenAmount::usomm(self.fee)
+        );
+        if !shrink {
+            return Err(reason);
+        }
+
+        bid.scaled_to(available)
+            .ok_or_else(|| format!("{reason}, and it can't be shrunk to fit"))
+    }
+
+    /// The wallet's state if placing `bid` leaves it with less than `threshold` uSOMM to bid with
+    pub fn low_balance(&self, bid: &Bid, threshold: u64) -> Option<LowBalance> {
+        let remaining = self.available().saturating_sub(bid.maximum_usomm_in.raw);
+        if remaining >= threshold as u128 {
+            return None;
+        }
+
+        Some(LowBalance {
+            auction_id: bid.auction_id,
+            balance: TokenAmount::usomm(self.balance),
+            remaining: TokenAmount::usomm(remaining),
+            threshold: TokenAmount::usomm(threshold as u128),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sommelier_auction::denom::Denom;
+
+    use super::*;
+    use crate::fixtures::bid_with;
+
+    fn bid(maximum_usomm_in: u128, minimum_tokens_out: u128) -> Bid {
+        bid_with(&Denom::USDC, maximum_usomm_in, minimum_tokens_out)
+    }
+
+    fn wallet(balance: u128, outstanding: u128) -> WalletBalance {
+        WalletBalance {
+            balance,
+            outstanding,
+            fee: 500,
+        }
+    }
+
+    #[test]
+    fn test_available() {
+        assert_eq!(wallet(10_000, 2000).available(), 7500);
+        assert_eq!(wallet(10_000, 0).available(), 9500);
+        assert_eq!(wallet(10_000, 20_000).available(), 0);
+        assert_eq!(wallet(100, 0).available(), 0);
+    }
+
+    #[test]
+    fn test_bid_that_fits_is_unchanged() {
+        let wallet = wallet(10_000, 2000);
+
+        assert_eq!(wallet.fit(&bid(7500, 75), false), Ok(bid(7500, 75)));
+        assert_eq!(wallet.fit(&bid(7500, 75), true), Ok(bid(7500, 75)));
+    }
+
+    #[test]
+    fn test_bid_that_does_not_fit_is_skipped() {
+        let reason = wallet(10_000, 2000).fit(&bid(8000, 80), false).unwrap_err();
+
+        assert!(reason.contains("exceeds the 7500usomm"), "{reason}");
+        assert!(reason.contains("2000usomm held"), "{reason}");
+    }
+
+    #[test]
+    fn test_bid_that_does_not_fit_is_shrunk() {
+        // the minimum out is cut in proportion, rounded down so the bid can still be filled
+        assert_eq!(
+            wallet(10_000, 2000).fit(&bid(8000, 80), true),
+            Ok(bid(7500, 75))
+        );
+        assert_eq!(
+            wallet(10_000, 2000).fit(&bid(8000, 81), true),
+            Ok(bid(7500, 75))
+        );
+    }
+
+    #[test]
+    fn test_bid_that_can_not_be_shrunk_is_skipped() {
+        // nothing available
+        let reason = wallet(10_000, 9500).fit(&bid(8000, 80), true).unwrap_err();
+        assert!(reason.ends_with("can't be shrunk to fit"), "{reason}");
+
+        // the fee takes all of it
+        let reason = wallet(400, 0).fit(&bid(8000, 80), true).unwrap_err();
+        assert!(reason.ends_with("can't be shrunk to fit"), "{reason}");
+    }
+
+    #[test]
+    fn test_low_balance() {
+        let wallet = wallet(10_000, 2000);
+
+        assert_eq!(wallet.low_balance(&bid(5000, 50), 2500), None);
+        assert_eq!(
+            wallet.low_balance(&bid(5001, 50), 2500),
+            Some(LowBalance {
+                auction_id: 1,
+                balance: TokenAmount::usomm(10_000),
+                remaining: TokenAmount::usomm(2499),
+                threshold: TokenAmount::usomm(2500),
+            })
+        );
+        assert_eq!(
+            wallet.low_balance(&bid(8000, 80), 1).unwrap().remaining,
+            TokenAmount::usomm(0)
+        );
+    }
+}
@@ -0,0 +1,846 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use sommelier_auction::{bid::Bid, client::BidTx, view::BidView, BidResult};
+use tracing::{info, warn};
+
+use crate::order::{Order, OrderEntry};
+
+/// Where an order is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    /// Waiting for an auction to meet the order
+    Pending,
+    /// A strategy decided to bid on the order and the bid is queued for submission
+    Triggered,
+    /// The bid was sent but its outcome isn't known. It must be reconciled with the chain before
+    /// the order can be bid on again.
+    Submitted,
+    /// The bid landed on chain and filled the whole order
+    Confirmed,
+    /// The bid landed on chain but bought less than the order asked for, such as when the auction
+    /// ran out of tokens
+    PartiallyFilled,
+    /// The bid was rejected before it was placed
+    Failed,
+    /// The order was dropped without bidding, such as when it no longer fits in the budget
+    Cancelled,
+    /// The order's expiry passed before it was bid on
+    Expired,
+}
+
+impl OrderStatus {
+    /// Whether the order can still lead to a bid
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Pending | OrderStatus::Triggered | OrderStatus::Submitted
+        )
+    }
+
+    /// Whether a bid for the order was placed, or may have been
+    pub fn has_bid(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Submitted | OrderStatus::Confirmed | OrderStatus::PartiallyFilled
+        )
+    }
+
+    fn can_become(&self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+
+        match self {
+            Pending => matches!(next, Triggered | Cancelled | Expired),
+            Triggered => matches!(next, Pending | Submitted | Failed | Cancelled),
+            Submitted => matches!(next, Pending | Confirmed | PartiallyFilled | Failed),
+            Confirmed | PartiallyFilled | Failed | Cancelled | Expired => false,
+        }
+    }
+}
+
+/// Why an order was cancelled, which decides whether a restart gives it another go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cancellation {
+    /// Something about the engine's setup, such as an unregistered denom, that a restart may fix
+    Setup,
+    /// The budget couldn't cover the order. A restart doesn't change that, so the order stays
+    /// cancelled.
+    Funds,
+}
+
+/// A status an order moved to and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub status: OrderStatus,
+    pub at: SystemTime,
+    /// Why the order moved to the status, if there's more to say than the status itself
+    pub reason: Option<String>,
+}
+
+/// An order and what has happened to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderRecord {
+    /// Identifies the order across restarts
+    pub id: String,
+    /// For follow-ups to partially filled orders, the id of the configured order they came from
+    #[serde(default)]
+    pub origin_id: Option<String>,
+    pub order: Order,
+    /// Time after which the order is no longer bid on
+    pub expires_at: Option<SystemTime>,
+    /// Whether a partial fill adds a follow-up order for what's left
+    #[serde(default)]
+    pub rebid_partial_fills: bool,
+    pub status: OrderStatus,
+    /// Why the order was cancelled, if it was
+    #[serde(default)]
+    pub cancellation: Option<Cancellation>,
+    /// Every status the order has been in, oldest first
+    pub history: Vec<StatusChange>,
+    /// The bid sent for the order, if any
+    pub bid: Option<Bid>,
+    /// The transaction the bid was sent in, by which its outcome can be looked up
+    #[serde(default)]
+    pub tx: Option<BidTx>,
+    /// The order's bid as recorded on chain once it's known
+    pub result: Option<BidView>,
+}
+
+impl OrderRecord {
+    fn new(id: String, entry: &OrderEntry) -> Self {
+        Self {
+            id,
+            origin_id: None,
+            order: entry.order.clone(),
+            expires_at: entry.expires_at(),
+            rebid_partial_fills: entry.rebid_partial_fills,
+            status: OrderStatus::Pending,
+            cancellation: None,
+            history: vec![StatusChange {
+                status: OrderStatus::Pending,
+                at: SystemTime::now(),
+                reason: None,
+            }],
+            bid: None,
+            tx: None,
+            result: None,
+        }
+    }
+
+    // A pending order for `remainder`, what this order's bid left unbought
+    fn follow_up(&self, id: String, remainder: Order, bid_id: u64) -> Self {
+        Self {
+            id,
+            origin_id: Some(self.origin_id.clone().unwrap_or_else(|| self.id.clone())),
+            order: remainder,
+            expires_at: self.expires_at,
+            rebid_partial_fills: self.rebid_partial_fills,
+            status: OrderStatus::Pending,
+            cancellation: None,
+            history: vec![StatusChange {
+                status: OrderStatus::Pending,
+                at: SystemTime::now(),
+                reason: Some(format!("remainder of order {} after bid {bid_id}", self.id)),
+            }],
+            bid: None,
+            tx: None,
+            result: None,
+        }
+    }
+
+    // Orders that never got as far as being submitted, were rejected, or were cancelled over
+    // something a restart may have fixed start over after a restart
+    fn resume(mut self) -> Self {
+        let restart = match self.status {
+            OrderStatus::Triggered | OrderStatus::Failed => true,
+            OrderStatus::Cancelled => self.cancellation == Some(Cancellation::Setup),
+            _ => false,
+        };
+        if restart {
+            self.status = OrderStatus::Pending;
+            self.cancellation = None;
+            self.history.push(StatusChange {
+                status: OrderStatus::Pending,
+                at: SystemTime::now(),
+                reason: Some("restarted".to_string()),
+            });
+            self.bid = None;
+            self.tx = None;
+        }
+        info!("resuming order {} in status {:?}", self.id, self.status);
+
+        self
+    }
+
+    /// When the order was added
+    pub fn created_at(&self) -> SystemTime {
+        self.history
+            .first()
+            .map(|c| c.at)
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+
+    /// When the order last changed status
+    pub fn updated_at(&self) -> SystemTime {
+        self.history
+            .last()
+            .map(|c| c.at)
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+
+    /// Whether the order's expiry has passed at `time`
+    pub fn is_expired_at(&self, time: SystemTime) -> bool {
+        self.expires_at.is_some_and(|expiry| time >= expiry)
+    }
+
+    fn set_status(&mut self, status: OrderStatus, reason: Option<String>) -> Result<()> {
+        if !self.status.can_become(status) {
+            return Err(eyre!(
+                "order {} can't go from {:?} to {:?}",
+                self.id,
+                self.status,
+                status
+            ));
+        }
+
+        self.status = status;
+        self.history.push(StatusChange {
+            status,
+            at: SystemTime::now(),
+            reason,
+        });
+
+        Ok(())
+    }
+}
+
+/// Every configured order and its lifecycle. With a path the book is written to a JSON file after
+/// every change so a restarted engine resumes where it left off instead of replaying the config.
+#[derive(Debug, Default)]
+pub struct OrderBook {
+    /// Without a path the book only lives in memory
+    path: Option<PathBuf>,
+    records: Vec<OrderRecord>,
+}
+
+impl OrderBook {
+    /// Loads the book at `path` if it exists and merges in the configured `entries`. An order keeps
+    /// its stored status unless the config now has a different order under its id. Orders that
+    /// never got as far as being submitted, were rejected, or were cancelled over the engine's
+    /// setup start over. Follow-ups to partially filled orders are kept as long as the order they
+    /// came from is. Orders that were bid on are kept even once they're gone from the config, so
+    /// what they spent still counts and outstanding bids get reconciled.
+    pub fn open(path: Option<PathBuf>, entries: &[OrderEntry]) -> Result<Self> {
+        let stored = match &path {
+            Some(path) if path.exists() => load(path)?,
+            _ => Vec::new(),
+        };
+
+        let mut records = Vec::<OrderRecord>::with_capacity(entries.len());
+        let mut resumed = Vec::new();
+        for entry in entries {
+            let id = entry.id();
+            if records.iter().any(|r| r.id == id) {
+                return Err(eyre!(
+                    "duplicate order id {id}. identical orders need ids of their own"
+                ));
+            }
+
+            let record = match stored.iter().find(|r| r.id == id) {
+                Some(record) if record.order == entry.order => {
+                    let mut record = record.clone();
+                    record.expires_at = entry.expires_at();
+                    record.rebid_partial_fills = entry.rebid_partial_fills;
+                    resumed.push(id.clone());
+
+                    record.resume()
+                }
+                Some(record) => {
+                    warn!(
+                        "order {id} changed in the config, starting it over. stored order was {:?}",
+                        record.order
+                    );
+
+                    OrderRecord::new(id, entry)
+                }
+                None => OrderRecord::new(id, entry),
+            };
+            records.push(record);
+        }
+
+        let (follow_ups, dropped): (Vec<OrderRecord>, Vec<OrderRecord>) =
+            stored.into_iter().partition(|r| {
+                r.origin_id
+                    .as_ref()
+                    .is_some_and(|origin| resumed.contains(origin))
+            });
+        for follow_up in follow_ups {
+            records.push(follow_up.resume());
+        }
+
+        let dropped_ids: Vec<String> = dropped.iter().map(|r| r.id.clone()).collect();
+        for mut record in dropped {
+            if resumed.contains(&record.id) || !record.status.has_bid() {
+                continue;
+            }
+
+            // the config has a different order under the id now
+            if records.iter().any(|r| r.id == record.id) {
+                let id = retired_id(&record.id, |id| {
+                    dropped_ids.iter().any(|d| d == id) || records.iter().any(|r| r.id == id)
+                });
+                warn!(
+                    "keeping order {} as {id} since it was bid on: {:?}",
+                    record.id, record.order
+                );
+                record.id = id;
+            }
+            records.push(record);
+        }
+
+        let book = Self { path, records };
+        book.save()?;
+
+        Ok(book)
+    }
+
+    pub fn orders(&self) -> &[OrderRecord] {
+        &self.records
+    }
+
+    pub fn get(&self, id: &str) -> Option<&OrderRecord> {
+        self.records.iter().find(|r| r.id == id)
+    }
+
+    /// Orders in `status`
+    pub fn with_status(&self, status: OrderStatus) -> impl Iterator<Item = &OrderRecord> {
+        self.records.iter().filter(move |r| r.status == status)
+    }
+
+    /// Orders waiting to be bid on
+    pub fn pending(&self) -> impl Iterator<Item = &OrderRecord> {
+        self.with_status(OrderStatus::Pending)
+    }
+
+    /// Records the bid a strategy decided to place for the order
+    pub fn trigger(&mut self, id: &str, bid: &Bid) -> Result<()> {
+        self.update(id, |record| {
+            record.set_status(OrderStatus::Triggered, None)?;
+            record.bid = Some(bid.clone());
+
+            Ok(())
+        })
+    }
+
+    /// Marks the order's bid as sent in `tx`, recording the bid as sent in case it changed since
+    /// it was triggered. Recorded before sending so a crash mid-submission can't lead to a second
+    /// bid.
+    pub fn submit(&mut self, id: &str, bid: &Bid, tx: &BidTx) -> Result<()> {
+        self.update(id, |record| {
+            record.set_status(OrderStatus::Submitted, None)?;
+            record.bid = Some(bid.clone());
+            record.tx = Some(tx.clone());
+
+            Ok(())
+        })
+    }
+
+    /// Links the order to its bid on chain, confirming it or marking it partially filled. A
+    /// partially filled order that rebids partial fills gets a pending follow-up order for what's
+    /// left, as long as it's enough to bid on given the auction's `minimum_bid_in_usomm`.
+    pub fn confirm(
+        &mut self,
+        id: &str,
+        result: &BidResult,
+        minimum_bid_in_usomm: u64,
+    ) -> Result<()> {
+        let view = BidView::try_from(result)?;
+        let record = self.get(id).ok_or_else(|| eyre!("no order with id {id}"))?;
+        let status = if record.order.is_partially_filled_by(&view) {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Confirmed
+        };
+        let follow_up = if record.rebid_partial_fills {
+            record
+                .order
+                .remainder(&view, minimum_bid_in_usomm)
+                .map(|remainder| record.follow_up(self.follow_up_id(record), remainder, result.id))
+        } else {
+            None
+        };
+
+        self.transact(|records| {
+            let record = records
+                .iter_mut()
+                .find(|r| r.id == id)
+                .ok_or_else(|| eyre!("no order with id {id}"))?;
+            record.set_status(status, None)?;
+            record.result = Some(view);
+
+            if let Some(follow_up) = follow_up {
+                if records.iter().any(|r| r.id == follow_up.id) {
+                    return Err(eyre!("duplicate order id {}", follow_up.id));
+                }
+
+                info!(
+                    "order {id} was partially filled, rebidding the remainder as order {}: {:?}",
+                    follow_up.id, follow_up.order
+                );
+                records.push(follow_up);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Marks the order's bid as rejected before it was placed
+    pub fn fail(&mut self, id: &str, reason: impl ToString) -> Result<()> {
+        self.update(id, |record| {
+            record.set_status(OrderStatus::Failed, Some(reason.to_string()))
+        })
+    }
+
+    /// Drops the order without bidding
+    pub fn cancel(
+        &mut self,
+        id: &str,
+        cancellation: Cancellation,
+        reason: impl ToString,
+    ) -> Result<()> {
+        self.update(id, |record| {
+            record.set_status(OrderStatus::Cancelled, Some(reason.to_string()))?;
+            record.cancellation = Some(cancellation);
+
+            Ok(())
+        })
+    }
+
+    /// Drops the order because its expiry passed
+    pub fn expire(&mut self, id: &str) -> Result<()> {
+        self.update(id, |record| record.set_status(OrderStatus::Expired, None))
+    }
+
+    /// Makes the order biddable again after its bid was found not to have been placed
+    pub fn reset(&mut self, id: &str, reason: impl ToString) -> Result<()> {
+        self.update(id, |record| {
+            record.set_status(OrderStatus::Pending, Some(reason.to_string()))?;
+            record.bid = None;
+            record.tx = None;
+
+            Ok(())
+        })
+    }
+
+    /// Ids of the bids in auction `auction_id` already linked to an order
+    pub fn linked_bid_ids(&self, auction_id: u32) -> Vec<u64> {
+        self.records
+            .iter()
+            .filter_map(|r| r.result.as_ref())
+            .filter(|result| result.auction_id == auction_id)
+            .map(|result| result.id)
+            .collect()
+    }
+
+    // Follow-ups are numbered under the configured order they came from: order `a`'s are `a/1`,
+    // `a/2` and so on
+    fn follow_up_id(&self, record: &OrderRecord) -> String {
+        let origin = record.origin_id.as_ref().unwrap_or(&record.id);
+        let count = self
+            .records
+            .iter()
+            .filter(|r| r.origin_id.as_ref() == Some(origin))
+            .count();
+
+        format!("{origin}/{}", count + 1)
+    }
+
+    // Applies `f` to the order and saves the book
+    fn update(&mut self, id: &str, f: impl FnOnce(&mut OrderRecord) -> Result<()>) -> Result<()> {
+        self.transact(|records| {
+            let record = records
+                .iter_mut()
+                .find(|r| r.id == id)
+                .ok_or_else(|| eyre!("no order with id {id}"))?;
+
+            f(record)
+        })
+    }
+
+    // Applies `f` to the orders and saves the book, leaving the orders untouched if either fails
+    fn transact(&mut self, f: impl FnOnce(&mut Vec<OrderRecord>) -> Result<()>) -> Result<()> {
+        let previous = self.records.clone();
+
+        let result = f(&mut self.records).and_then(|_| self.save());
+        if result.is_err() {
+            self.records = previous;
+        }
+
+        result
+    }
+
+    // Writes to a temporary file first so a crash mid-write can't corrupt the book
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.records)?)?;
+        std::fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+}
+
+// Orders replaced in the config are kept as `a@1`, `a@2` and so on
+fn retired_id(id: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    (1..)
+        .map(|n| format!("{id}@{n}"))
+        .find(|retired| !is_taken(retired))
+        .unwrap()
+}
+
+fn load(path: &Path) -> Result<Vec<OrderRecord>> {
+    let contents = std::fs::read_to_string(path)?;
+
+    serde_json::from_str(&contents)
+        .map_err(|err| eyre!("failed to parse order book {}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use sommelier_auction::amount::TokenAmount;
+
+    use super::*;
+    use crate::fixtures::{bid, bid_result, entry, temp_path, tx};
+
+    // a book with order `a` for 1000 usomm, bid on and rebidding partial fills
+    fn submitted_book() -> OrderBook {
+        let mut entry = entry(Some("a"), 1000);
+        entry.rebid_partial_fills = true;
+        let mut book = OrderBook::open(None, &[entry]).unwrap();
+        book.trigger("a", &bid(1000)).unwrap();
+        book.submit("a", &bid(1000), &tx("a")).unwrap();
+
+        book
+    }
+
+    #[test]
+    fn test_confirm_ignores_rounding_dust() {
+        let mut book = submitted_book();
+
+        // 1000 usomm buys 333 units at 3 usomm, leaving 1 usomm unspent
+        book.confirm("a", &bid_result(1, &bid(1000), 333, "3.0", 999), 0)
+            .unwrap();
+
+        assert_eq!(book.get("a").unwrap().status, OrderStatus::Confirmed);
+        assert_eq!(book.orders().len(), 1);
+        assert!(!book.orders().iter().any(|r| r.status.is_open()));
+    }
+
+    #[test]
+    fn test_confirm_rebids_partial_fill() {
+        let mut book = submitted_book();
+
+        book.confirm("a", &bid_result(1, &bid(1000), 100, "3.0", 300), 500)
+            .unwrap();
+
+        let record = book.get("a").unwrap();
+        assert_eq!(record.status, OrderStatus::PartiallyFilled);
+        assert_eq!(record.result.as_ref().unwrap().id, 1);
+
+        let follow_up = book.get("a/1").unwrap();
+        assert_eq!(follow_up.status, OrderStatus::Pending);
+        assert_eq!(follow_up.origin_id.as_deref(), Some("a"));
+        assert!(follow_up.rebid_partial_fills);
+        assert_eq!(
+            follow_up.order.maximum_usomm_in(6),
+            Some(TokenAmount::usomm(700))
+        );
+
+        // follow-ups of follow-ups are numbered under the configured order
+        book.trigger("a/1", &bid(700)).unwrap();
+        book.submit("a/1", &bid(700), &tx("a/1")).unwrap();
+        book.confirm("a/1", &bid_result(2, &bid(700), 100, "3.0", 300), 0)
+            .unwrap();
+        let follow_up = book.get("a/2").unwrap();
+        assert_eq!(follow_up.origin_id.as_deref(), Some("a"));
+        assert_eq!(
+            follow_up.order.maximum_usomm_in(6),
+            Some(TokenAmount::usomm(400))
+        );
+    }
+
+    #[test]
+    fn test_confirm_skips_remainder_below_minimum_bid() {
+        let mut book = submitted_book();
+
+        book.confirm("a", &bid_result(1, &bid(1000), 100, "3.0", 300), 701)
+            .unwrap();
+
+        assert_eq!(book.get("a").unwrap().status, OrderStatus::PartiallyFilled);
+        assert_eq!(book.orders().len(), 1);
+    }
+
+    #[test]
+    fn test_confirm_without_rebidding() {
+        let mut book = OrderBook::open(None, &[entry(Some("a"), 1000)]).unwrap();
+        book.trigger("a", &bid(1000)).unwrap();
+        book.submit("a", &bid(1000), &tx("a")).unwrap();
+
+        book.confirm("a", &bid_result(1, &bid(1000), 100, "3.0", 300), 0)
+            .unwrap();
+
+        assert_eq!(book.get("a").unwrap().status, OrderStatus::PartiallyFilled);
+        assert_eq!(book.orders().len(), 1);
+    }
+
+    #[test]
+    fn test_derived_ids_survive_reordering() {
+        let path = temp_path("derived-ids");
+        let (a, b) = (entry(None, 1000), entry(None, 2000));
+
+        let mut book = OrderBook::open(Some(path.clone()), &[a.clone(), b.clone()]).unwrap();
+        assert_ne!(a.id(), b.id());
+        book.cancel(&a.id(), Cancellation::Funds, "over budget")
+            .unwrap();
+
+        let book = OrderBook::open(Some(path.clone()), &[b.clone(), a.clone()]).unwrap();
+        assert_eq!(book.get(&a.id()).unwrap().status, OrderStatus::Cancelled);
+        assert_eq!(book.get(&b.id()).unwrap().status, OrderStatus::Pending);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_resume_keeps_funds_cancellations() {
+        let path = temp_path("cancellations");
+        let entries = [entry(Some("setup"), 1000), entry(Some("funds"), 1000)];
+
+        let mut book = OrderBook::open(Some(path.clone()), &entries).unwrap();
+        book.cancel("setup", Cancellation::Setup, "unregistered denom")
+            .unwrap();
+        book.cancel("funds", Cancellation::Funds, "over budget")
+            .unwrap();
+
+        let book = OrderBook::open(Some(path.clone()), &entries).unwrap();
+        let setup = book.get("setup").unwrap();
+        assert_eq!(setup.status, OrderStatus::Pending);
+        assert_eq!(setup.cancellation, None);
+        let funds = book.get("funds").unwrap();
+        assert_eq!(funds.status, OrderStatus::Cancelled);
+        assert_eq!(funds.cancellation, Some(Cancellation::Funds));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_orders_bid_on_are_kept() {
+        let path = temp_path("kept");
+        let entries = [
+            entry(Some("removed"), 1000),
+            entry(Some("changed"), 1000),
+            entry(Some("unbid"), 1000),
+        ];
+
+        let mut book = OrderBook::open(Some(path.clone()), &entries).unwrap();
+        for id in ["removed", "changed"] {
+            book.trigger(id, &bid(1000)).unwrap();
+            book.submit(id, &bid(1000), &tx(id)).unwrap();
+        }
+
+        let entries = [entry(Some("changed"), 2000)];
+        let book = OrderBook::open(Some(path.clone()), &entries).unwrap();
+        let ids: Vec<&str> = book.orders().iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["changed", "removed", "changed@1"]);
+        assert_eq!(book.get("changed").unwrap().status, OrderStatus::Pending);
+        assert_eq!(book.get("removed").unwrap().status, OrderStatus::Submitted);
+        assert_eq!(book.get("changed@1").unwrap().bid, Some(bid(1000)));
+
+        // a second change keeps both earlier versions
+        let mut book = book;
+        book.trigger("changed", &bid(2000)).unwrap();
+        book.submit("changed", &bid(2000), &tx("changed")).unwrap();
+        let book = OrderBook::open(Some(path.clone()), &[entry(Some("changed"), 3000)]).unwrap();
+        let mut ids: Vec<&str> = book.orders().iter().map(|r| r.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["changed", "changed@1", "changed@2", "removed"]);
+        assert_eq!(book.get("changed@2").unwrap().bid, Some(bid(2000)));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_can_become() {
+        use OrderStatus::*;
+
+        let all = [
+            Pending,
+            Triggered,
+            Submitted,
+            Confirmed,
+            PartiallyFilled,
+            Failed,
+            Cancelled,
+            Expired,
+        ];
+        let allowed = [
+            (Pending, Triggered),
+            (Pending, Cancelled),
+            (Pending, Expired),
+            (Triggered, Pending),
+            (Triggered, Submitted),
+            (Triggered, Failed),
+            (Triggered, Cancelled),
+            (Submitted, Pending),
+            (Submitted, Confirmed),
+            (Submitted, PartiallyFilled),
+            (Submitted, Failed),
+        ];
+
+        for from in all {
+            for to in all {
+                assert_eq!(
+                    from.can_become(to),
+                    allowed.contains(&(from, to)),
+                    "{from:?} -> {to:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_transition_is_rejected() {
+        let mut book = OrderBook::open(None, &[entry(Some("a"), 1000)]).unwrap();
+
+        assert!(book.submit("a", &bid(1000), &tx("a")).is_err());
+        assert!(book.reset("a", "never bid").is_err());
+
+        let record = book.get("a").unwrap();
+        assert_eq!(record.status, OrderStatus::Pending);
+        assert_eq!(record.bid, None);
+        assert_eq!(record.history.len(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_ids_are_rejected() {
+        let err =
+            OrderBook::open(None, &[entry(Some("a"), 1000), entry(Some("a"), 2000)]).unwrap_err();
+        assert!(err.to_string().contains("duplicate order id a"), "{err}");
+
+        // identical orders without ids derive the same id
+        assert!(OrderBook::open(None, &[entry(None, 1000), entry(None, 1000)]).is_err());
+        assert!(OrderBook::open(None, &[entry(None, 1000), entry(Some("b"), 1000)]).is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = temp_path("round-trip");
+        let entries = [entry(Some("a"), 1000), entry(Some("b"), 2000)];
+
+        let mut book = OrderBook::open(Some(path.clone()), &entries).unwrap();
+        book.trigger("a", &bid(1000)).unwrap();
+        book.submit("a", &bid(1000), &tx("a")).unwrap();
+        book.expire("b").unwrap();
+
+        assert_eq!(load(&path).unwrap(), book.orders());
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_resume() {
+        let path = temp_path("resume");
+        let entries = [
+            entry(Some("pending"), 1000),
+            entry(Some("triggered"), 1000),
+            entry(Some("submitted"), 1000),
+            entry(Some("failed"), 1000),
+            entry(Some("expired"), 1000),
+        ];
+
+        let mut book = OrderBook::open(Some(path.clone()), &entries).unwrap();
+        for id in ["triggered", "submitted", "failed"] {
+            book.trigger(id, &bid(1000)).unwrap();
+        }
+        for id in ["submitted", "failed"] {
+            book.submit(id, &bid(1000), &tx(id)).unwrap();
+        }
+        book.fail("failed", "rejected").unwrap();
+        book.expire("expired").unwrap();
+        drop(book);
+
+        let book = OrderBook::open(Some(path.clone()), &entries).unwrap();
+        let status = |id: &str| book.get(id).unwrap().status;
+        assert_eq!(status("pending"), OrderStatus::Pending);
+        assert_eq!(status("triggered"), OrderStatus::Pending);
+        assert_eq!(status("submitted"), OrderStatus::Submitted);
+        assert_eq!(status("failed"), OrderStatus::Pending);
+        assert_eq!(status("expired"), OrderStatus::Expired);
+
+        // restarted orders forget their bid but keep their history
+        let failed = book.get("failed").unwrap();
+        assert_eq!(failed.bid, None);
+        assert_eq!(failed.tx, None);
+        let last = failed.history.last().unwrap();
+        assert_eq!(last.reason.as_deref(), Some("restarted"));
+        assert_eq!(failed.history.len(), 5);
+        assert_eq!(book.get("submitted").unwrap().bid, Some(bid(1000)));
+        assert_eq!(book.get("submitted").unwrap().tx, Some(tx("submitted")));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_changed_order_starts_over() {
+        let path = temp_path("changed");
+
+        let mut book = OrderBook::open(Some(path.clone()), &[entry(Some("a"), 1000)]).unwrap();
+        book.expire("a").unwrap();
+
+        // only the expiry changed, which doesn't restart the order
+        let mut expiring = entry(Some("a"), 1000);
+        expiring.expires_at = Some(1_700_000_000);
+        let book = OrderBook::open(Some(path.clone()), &[expiring]).unwrap();
+        let record = book.get("a").unwrap();
+        assert_eq!(record.status, OrderStatus::Expired);
+        assert_eq!(
+            record.expires_at,
+            Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000))
+        );
+
+        let book = OrderBook::open(Some(path.clone()), &[entry(Some("a"), 2000)]).unwrap();
+        let record = book.get("a").unwrap();
+        assert_eq!(record.status, OrderStatus::Pending);
+        assert_eq!(record.order, entry(None, 2000).order);
+        assert_eq!(record.history.len(), 1);
+        // an order that was never bid on isn't kept
+        assert_eq!(book.orders().len(), 1);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_save_rolls_back() {
+        let dir = std::env::temp_dir().join(format!("order-book-rollback-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("orders.json");
+
+        let mut book = OrderBook::open(Some(path.clone()), &[entry(Some("a"), 1000)]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(book.trigger("a", &bid(1000)).is_err());
+        let record = book.get("a").unwrap();
+        assert_eq!(record.status, OrderStatus::Pending);
+        assert_eq!(record.bid, None);
+        assert_eq!(record.history.len(), 1);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use eyre::{eyre, Result};
+use sommelier_auction::{amount::TokenAmount, bid::Bid, denom::Denom};
+
+use crate::book::{OrderRecord, OrderStatus};
+
+/// Tracks usomm spent on bids against an optional global cap and optional per-denom caps.
+///
+/// A bid's full `maximum_usomm_in` is reserved when it is sent. Once the chain reports what the
+/// bid actually paid, that amount is committed and the rest is refunded. Bids whose outcome is
+/// unknown, such as ones that timed out, stay reserved until they're reconciled so the budget errs
+/// on the side of caution.
+#[derive(Debug, Clone, Default)]
+pub struct UsommBudget {
+    total_cap: Option<u128>,
+    denom_caps: HashMap<Denom, u128>,
+    reserved: HashMap<Denom, u128>,
+    committed: HashMap<Denom, u128>,
+    refunded: HashMap<Denom, u128>,
+}
+
+impl UsommBudget {
+    pub fn new(total_cap: Option<u64>, denom_caps: HashMap<Denom, u64>) -> Self {
+        Self {
+            total_cap: total_cap.map(u128::from),
+            denom_caps: denom_caps
+                .into_iter()
+                .map(|(d, cap)| (d, cap as u128))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// usomm held by bids that haven't settled
+    pub fn reserved(&self) -> TokenAmount {
+        TokenAmount::usomm(self.reserved.values().sum())
+    }
+
+    /// usomm paid by settled bids
+    pub fn committed(&self) -> TokenAmount {
+        TokenAmount::usomm(self.committed.values().sum())
+    }
+
+    /// usomm returned to the budget because bids paid less than their maximum or weren't placed
+    pub fn refunded(&self) -> TokenAmount {
+        TokenAmount::usomm(self.refunded.values().sum())
+    }
+
+    /// usomm counted against the budget, both reserved and committed
+    pub fn spent(&self) -> TokenAmount {
+        TokenAmount::usomm(self.spent_raw())
+    }
+
+    /// usomm counted against the budget for bids on `denom`
+    pub fn spent_on(&self, denom: &Denom) -> TokenAmount {
+        TokenAmount::usomm(self.spent_on_raw(denom))
+    }
+
+    /// The most usomm a bid on `denom` can use, or `None` if neither cap applies
+    pub fn available(&self, denom: &Denom) -> Option<TokenAmount> {
+        let total = self
+            .total_cap
+            .map(|cap| cap.saturating_sub(self.spent_raw()));
+        let denom_total = self
+            .denom_caps
+            .get(denom)
+            .map(|cap| cap.saturating_sub(self.spent_on_raw(denom)));
+
+        match (total, denom_total) {
+            (None, None) => None,
+            (Some(a), None) | (None, Some(a)) => Some(TokenAmount::usomm(a)),
+            (Some(a), Some(b)) => Some(TokenAmount::usomm(a.min(b))),
+        }
+    }
+
+    /// Reserves the bid's maximum usomm, failing with the reason if it doesn't fit in the budget
+    pub fn reserve(&mut self, bid: &Bid) -> Result<()> {
+        let amount = bid.maximum_usomm_in.raw;
+        if let Some(available) = self.available(&bid.fee_token) {
+            if amount > available.raw {
+                return Err(eyre!(
+                    "bid of {} on {} exceeds the remaining budget of {available}",
+                    bid.maximum_usomm_in,
+                    bid.fee_token
+                ));
+            }
+        }
+
+        *self.reserved.entry(bid.fee_token.clone()).or_default() += amount;
+
+        Ok(())
+    }
+
+    /// Counts the bids of orders from before a restart: what landed bids paid is committed and bids
+    /// whose outcome isn't known are reserved, whether or not they fit in the budget, since they
+    /// may already have been placed
+    pub fn restore(&mut self, records: &[OrderRecord]) {
+        for record in records {
+            match (record.status, &record.bid, &record.result) {
+                (OrderStatus::Confirmed | OrderStatus::PartiallyFilled, _, Some(result)) => {
+                    *self
+                        .committed
+                        .entry(result.fee_token().clone())
+                        .or_default() += result.total_usomm_paid.raw;
+                }
+                (OrderStatus::Submitted, Some(bid), _) => {
+                    *self.reserved.entry(bid.fee_token.clone()).or_default() +=
+                        bid.maximum_usomm_in.raw;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves a reserved bid to committed using the usomm the chain says it paid, refunding the
+    /// difference
+    pub fn settle(&mut self, bid: &Bid, total_usomm_paid: &TokenAmount) {
+        let reserved = self.take_reservation(bid);
+        let paid = total_usomm_paid.raw.min(reserved);
+
+        *self.committed.entry(bid.fee_token.clone()).or_default() += paid;
+        *self.refunded.entry(bid.fee_token.clone()).or_default() += reserved - paid;
+    }
+
+    /// Refunds a reserved bid that was never placed
+    pub fn release(&mut self, bid: &Bid) {
+        let reserved = self.take_reservation(bid);
+
+        *self.refunded.entry(bid.fee_token.clone()).or_default() += reserved;
+    }
+
+    fn take_reservation(&mut self, bid: &Bid) -> u128 {
+        let reserved = self.reserved.entry(bid.fee_token.clone()).or_default();
+        let amount = bid.maximum_usomm_in.raw.min(*reserved);
+        *reserved -= amount;
+
+        amount
+    }
+
+    fn spent_raw(&self) -> u128 {
+        self.reserved.values().chain(self.committed.values()).sum()
+    }
+
+    fn spent_on_raw(&self, denom: &Denom) -> u128 {
+        self.reserved.get(denom).copied().unwrap_or_default()
+            + self.committed.get(denom).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::bid_with;
+
+    #[test]
+    fn test_uncapped() {
+        let mut budget = UsommBudget::default();
+
+        budget
+            .reserve(&bid_with(&Denom::USDC, u64::MAX as u128, 1))
+            .unwrap();
+
+        assert_eq!(budget.available(&Denom::USDC), None);
+        assert_eq!(budget.spent().raw, u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_total_cap() {
+        let mut budget = UsommBudget::new(Some(1000), HashMap::new());
+
+        budget.reserve(&bid_with(&Denom::USDC, 600, 1)).unwrap();
+        assert_eq!(budget.available(&Denom::WETH).unwrap().raw, 400);
+
+        assert!(budget.reserve(&bid_with(&Denom::WETH, 401, 1)).is_err());
+        budget.reserve(&bid_with(&Denom::WETH, 400, 1)).unwrap();
+
+        assert_eq!(budget.available(&Denom::USDC).unwrap().raw, 0);
+        assert_eq!(budget.reserved().raw, 1000);
+        assert_eq!(budget.spent_on(&Denom::USDC).raw, 600);
+        assert_eq!(budget.spent_on(&Denom::WETH).raw, 400);
+    }
+
+    #[test]
+    fn test_denom_caps() {
+        let caps = HashMap::from([(Denom::USDC, 300), (Denom::WETH, 800)]);
+        let mut budget = UsommBudget::new(Some(1000), caps);
+
+        // the denom cap is tighter than the total cap
+        assert_eq!(budget.available(&Denom::USDC).unwrap().raw, 300);
+        assert!(budget.reserve(&bid_with(&Denom::USDC, 301, 1)).is_err());
+        budget.reserve(&bid_with(&Denom::USDC, 300, 1)).unwrap();
+
+        // the total cap is tighter than the denom cap
+        assert_eq!(budget.available(&Denom::WETH).unwrap().raw, 700);
+        assert!(budget.reserve(&bid_with(&Denom::WETH, 701, 1)).is_err());
+
+        // denoms without a cap are only held to the total
+        assert_eq!(budget.available(&Denom::DAI).unwrap().raw, 700);
+    }
+
+    #[test]
+    fn test_denom_cap_without_total() {
+        let caps = HashMap::from([(Denom::USDC, 300)]);
+        let budget = UsommBudget::new(None, caps);
+
+        assert_eq!(budget.available(&Denom::USDC).unwrap().raw, 300);
+        assert_eq!(budget.available(&Denom::WETH), None);
+    }
+
+    #[test]
+    fn test_settle_refunds_unpaid() {
+        let mut budget = UsommBudget::new(Some(1000), HashMap::new());
+        let placed = bid_with(&Denom::USDC, 600, 1);
+        budget.reserve(&placed).unwrap();
+
+        budget.settle(&placed, &TokenAmount::usomm(450));
+
+        assert_eq!(budget.reserved().raw, 0);
+        assert_eq!(budget.committed().raw, 450);
+        assert_eq!(budget.refunded().raw, 150);
+        assert_eq!(budget.available(&Denom::USDC).unwrap().raw, 550);
+    }
+
+    #[test]
+    fn test_settle_never_commits_more_than_reserved() {
+        let mut budget = UsommBudget::new(Some(1000), HashMap::new());
+        let placed = bid_with(&Denom::USDC, 600, 1);
+        budget.reserve(&placed).unwrap();
+
+        budget.settle(&placed, &TokenAmount::usomm(700));
+
+        assert_eq!(budget.committed().raw, 600);
+        assert_eq!(budget.refunded().raw, 0);
+    }
+
+    #[test]
+    fn test_release() {
+        let mut budget = UsommBudget::new(Some(1000), HashMap::new());
+        let placed = bid_with(&Denom::USDC, 600, 1);
+        let other = bid_with(&Denom::WETH, 100, 1);
+        budget.reserve(&placed).unwrap();
+        budget.reserve(&other).unwrap();
+
+        budget.release(&placed);
+
+        assert_eq!(budget.reserved().raw, 100);
+        assert_eq!(budget.committed().raw, 0);
+        assert_eq!(budget.refunded().raw, 600);
+        assert_eq!(budget.spent_on(&Denom::USDC).raw, 0);
+        assert_eq!(budget.available(&Denom::USDC).unwrap().raw, 900);
+
+        // releasing a bid that isn't reserved has nothing to refund
+        budget.release(&placed);
+        assert_eq!(budget.refunded().raw, 600);
+        assert_eq!(budget.reserved().raw, 100);
+    }
+}
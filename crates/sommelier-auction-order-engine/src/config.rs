@@ -1,4 +1,8 @@
-use crate::order::Order;
+use std::collections::HashMap;
+
+use sommelier_auction::{dec::Dec, denom::Denom, registry::DenomInfo, version::VersionCheck};
+
+use crate::order::OrderEntry;
 
 #[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct Config {
@@ -6,10 +10,41 @@ pub struct Config {
     pub grpc_endpoint: Option<String>,
     /// Optional RPC endpoint. Used for submitting bids.
     pub rpc_endpoint: Option<String>,
-    /// The maximum amount of USOMM that can be spent on bids
-    //    pub total_usomm_budget: u64,
+    /// The maximum amount of USOMM that can be spent on bids across all orders
+    pub total_usomm_budget: Option<u64>,
+    /// The maximum amount of USOMM that can be spent on bids for each denom
+    #[serde(default)]
+    pub denom_usomm_budgets: HashMap<Denom, u64>,
+    /// Cut bids down to the wallet's usomm balance instead of skipping them when the balance
+    /// can't cover them
+    #[serde(default)]
+    pub shrink_bids_to_balance: bool,
+    /// Warn when the wallet's usomm balance left after a bid falls below this many usomm
+    pub low_balance_warning_usomm: Option<u64>,
+    /// Optional gas price, in usomm per unit of gas, paid by bid transactions. Defaults to 0.025.
+    pub gas_price_usomm: Option<Dec>,
     /// The orders loaded in from a orderfile
-    pub orders: Vec<Order>,
+    pub orders: Vec<OrderEntry>,
+    /// Optional path to a JSON file the order book is kept in. On restart orders resume from it
+    /// instead of being bid on again. Orders are matched to stored ones by their id.
+    pub order_store_path: Option<String>,
+    /// Optional path to a TOML or JSON denom registry file. Entries are added to the builtin
+    /// registry, replacing builtin entries for the same denom.
+    pub denom_registry_path: Option<String>,
+    /// Additional denoms to register. These take precedence over the registry file.
+    #[serde(default)]
+    pub denoms: Vec<DenomInfo>,
+    /// Check registered decimals against the chain's bank metadata and auction token prices at
+    /// startup. Orders for denoms with mismatched decimals are dropped.
+    #[serde(default)]
+    pub verify_decimals: bool,
     /// Key for signing and spending wallet
     pub key_path: Option<String>,
+    /// Optional external signer endpoint, either tcp://host:port or unix:///path/to/socket. Takes
+    /// precedence over key_path and the mnemonic environment variable.
+    pub remote_signer: Option<String>,
+    /// What to do if the node runs an unsupported Sommelier version: "warn" (default), "fail" or
+    /// "skip"
+    #[serde(default)]
+    pub version_check: VersionCheck,
 }
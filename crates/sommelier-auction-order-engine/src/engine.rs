@@ -1,27 +1,72 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 use eyre::Result;
 use sommelier_auction::{
-    bid::Bid, client::Client, denom::Denom, parameters::AuctionParameters, AccountInfo,
+    bid::Bid,
+    client::{Client, DEFAULT_GAS_PRICE_USOMM},
+    dec::Dec,
+    denom::Denom,
+    parameters::AuctionParameters,
+    registry::DenomRegistry,
+    signer::{BidSigner, RemoteSigner},
+    validation::BidValidationError,
+    version::VersionCheck,
+    view::BidView,
+    AccountInfo,
+};
+use tokio::sync::{broadcast, mpsc::Sender};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    balance::WalletBalance,
+    book::{Cancellation, OrderBook, OrderRecord, OrderStatus},
+    budget::UsommBudget,
+    config::Config,
+    events::EngineEvent,
+    reconcile::{OutstandingBids, Reconciler, UncertainBid},
+    strategy::{BidIntent, Strategy},
+    threshold::ThresholdStrategy,
+    watcher::Watcher,
 };
-use tracing::{debug, error, info};
 
-use crate::{config::Config, order::Order, watcher::Watcher};
+// number of bids strategies can queue up before the watcher waits on submission
+const BID_CHANNEL_CAPACITY: usize = 32;
+// number of events kept for subscribers that fall behind
+const EVENT_CHANNEL_CAPACITY: usize = 16;
 
 pub struct OrderEngine {
-    pub orders: HashMap<Denom, Vec<Order>>,
+    // every configured order and its lifecycle, persisted so restarts don't bid twice
+    pub book: Arc<Mutex<OrderBook>>,
+    pub registry: DenomRegistry,
     pub client: Option<Client>,
     pub grpc_endpoint: String,
     // cache of USD prices of each denom
     pub prices: HashMap<Denom, f64>,
-    // max amount of usomm allowed to use on bids. if there are bids that haven't been submitted
-    // when this is reached, they will be cancelled.
     pub rpc_endpoint: String,
-    //    pub total_usomm_budget: u64,
-    // total amount of usomm that has been spent on bids. this value can never exceed total_usomm_budget
-    pub total_usomm_spent: u128,
+    // usomm allowed to be used on bids and how much has been spent. bids that don't fit in the
+    // budget are cancelled.
+    pub budget: Arc<Mutex<UsommBudget>>,
+    // cut bids down to the wallet balance instead of skipping them
+    pub shrink_bids_to_balance: bool,
+    pub low_balance_warning_usomm: Option<u64>,
+    // usomm per unit of gas paid by bid transactions
+    pub gas_price_usomm: Dec,
     pub auction_parameters: Option<AuctionParameters>,
     pub signer_key_path: Option<String>,
+    pub remote_signer: Option<String>,
+    // signer provided by the caller. takes precedence over any signer settings in the config.
+    pub signer: Option<Box<dyn BidSigner>>,
+    pub version_check: VersionCheck,
+    pub verify_decimals: bool,
+    // strategies run alongside the one built from the configured orders
+    pub strategies: Vec<Box<dyn Strategy>>,
+    // bids sent to the reconciler that it hasn't resolved yet
+    outstanding: OutstandingBids,
+    events: broadcast::Sender<EngineEvent>,
 }
 
 impl OrderEngine {
@@ -37,41 +82,392 @@ impl OrderEngine {
             sommelier_auction::client::DEFAULT_GRPC_ENDPOINT.to_string()
         };
 
-        // load orders
-        let mut orders = HashMap::<Denom, Vec<Order>>::new();
-        config
-            .orders
-            .into_iter()
-            .for_each(|order| match orders.get_mut(&order.fee_token) {
-                Some(v) => v.push(order),
-                None => {
-                    orders.insert(order.fee_token, vec![order]);
-                }
-            });
+        // load denoms
+        let mut registry = DenomRegistry::builtin();
+        if let Some(path) = config.denom_registry_path {
+            registry.extend(DenomRegistry::load(&path).expect("failed to load denom registry"));
+        }
+        registry
+            .extend(DenomRegistry::from_entries(config.denoms).expect("invalid denom in config"));
+
+        // load orders, resuming any previous state
+        let mut book = OrderBook::open(config.order_store_path.map(PathBuf::from), &config.orders)
+            .expect("failed to open order book");
+        let unregistered: Vec<Denom> = book
+            .pending()
+            .map(|record| record.order.fee_token().clone())
+            // IBC denoms are checked against their denom trace when the engine starts, which
+            // says what to register them with
+            .filter(|denom| !registry.contains(denom) && !denom.is_ibc())
+            .collect();
+        for denom in unregistered {
+            error!(
+                "ignoring order for unregistered denom {}. add it to the denom registry to bid on it.",
+                denom
+            );
+            cancel_pending_orders(&mut book, &denom, "unregistered denom");
+        }
 
-        debug!("loaded orders: {:?}", orders);
+        debug!("loaded orders: {:?}", book.orders());
+
+        // bids from before a restart still count against the budget
+        let mut budget = UsommBudget::new(config.total_usomm_budget, config.denom_usomm_budgets);
+        budget.restore(book.orders());
 
         Self {
-            orders,
+            book: Arc::new(Mutex::new(book)),
+            registry,
             client: None,
             grpc_endpoint,
             prices: HashMap::new(),
             rpc_endpoint,
-            //            total_usomm_budget: config.total_usomm_budget,
-            total_usomm_spent: 0,
+            budget: Arc::new(Mutex::new(budget)),
+            shrink_bids_to_balance: config.shrink_bids_to_balance,
+            low_balance_warning_usomm: config.low_balance_warning_usomm,
+            gas_price_usomm: config.gas_price_usomm.unwrap_or(DEFAULT_GAS_PRICE_USOMM),
             auction_parameters: None,
             signer_key_path: config.key_path,
+            remote_signer: config.remote_signer,
+            signer: None,
+            version_check: config.version_check,
+            verify_decimals: config.verify_decimals,
+            strategies: Vec::new(),
+            outstanding: OutstandingBids::default(),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Use the provided signer for bids instead of the one described by the config
+    pub fn with_signer(mut self, signer: impl BidSigner + 'static) -> Self {
+        self.signer = Some(Box::new(signer));
+        self
+    }
+
+    /// Run `strategy` in addition to the threshold strategy built from the configured orders
+    pub fn with_strategy(mut self, strategy: impl Strategy + 'static) -> Self {
+        self.strategies.push(Box::new(strategy));
+        self
+    }
+
+    /// Receive the events the engine emits while it runs, such as the wallet running low
+    pub fn subscribe(&self) -> broadcast::Receiver<EngineEvent> {
+        self.events.subscribe()
+    }
+
+    // The smallest bid the auction module accepts. Without the auction parameters follow-ups to
+    // partial fills are only held to buying a whole base unit.
+    fn minimum_bid_in_usomm(&self) -> u64 {
+        self.auction_parameters
+            .as_ref()
+            .map(|p| p.minimum_bid_in_usomm)
+            .unwrap_or_default()
+    }
+
+    // Fee tokens of the orders still waiting to be bid on
+    fn order_denoms(&self) -> Vec<Denom> {
+        let mut denoms: Vec<Denom> = self
+            .book
+            .lock()
+            .unwrap()
+            .pending()
+            .map(|record| record.order.fee_token().clone())
+            .collect();
+        denoms.sort();
+        denoms.dedup();
+
+        denoms
+    }
+
+    // Checks the IBC denoms orders refer to against their traces on chain, so an entry for the
+    // wrong token isn't traded. Orders for denoms that can't be verified are dropped.
+    async fn verify_ibc_denoms(&mut self, client: &mut Client) {
+        let ibc_denoms: Vec<Denom> = self
+            .order_denoms()
+            .into_iter()
+            .filter(Denom::is_ibc)
+            .collect();
+
+        for denom in ibc_denoms {
+            match client.verify_ibc_denom(&mut self.registry, &denom).await {
+                Ok(info) => info!(
+                    "verified {denom} is {} ({})",
+                    info.symbol,
+                    info.ibc_trace.map(|t| t.to_string()).unwrap_or_default()
+                ),
+                Err(err) => {
+                    error!("failed to verify {denom}, dropping its orders: {err:?}");
+                    cancel_pending_orders(
+                        &mut self.book.lock().unwrap(),
+                        &denom,
+                        "unverified IBC denom",
+                    );
+                }
+            }
+        }
+    }
+
+    // Refuses to trade denoms whose registered decimals disagree with the chain. A wrong exponent
+    // would misprice bids by orders of magnitude.
+    async fn verify_decimals(&mut self, client: &mut Client) -> Result<()> {
+        let denoms = self.order_denoms();
+        let checks = client.check_decimals(&self.registry, &denoms).await?;
+
+        for check in checks {
+            if !check.is_consistent() {
+                error!(
+                    "decimals mismatch for {}: registry = {}, bank metadata = {:?}, token price exponent = {:?}. dropping its orders.",
+                    check.denom, check.registry, check.bank_metadata, check.token_price
+                );
+                cancel_pending_orders(
+                    &mut self.book.lock().unwrap(),
+                    &check.denom,
+                    "decimals mismatch",
+                );
+            } else if !check.is_verified() {
+                warn!(
+                    "no on-chain decimals found for {}, using registry value {}",
+                    check.denom, check.registry
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    // Hands orders whose bids were sent before a restart, without their outcome being recorded, to
+    // the reconciler. Orders that can't be checked are left alone and won't be bid on.
+    async fn reconcile_submitted_orders(&self, reconcile_tx: Option<&Sender<UncertainBid>>) {
+        let submitted: Vec<OrderRecord> = self
+            .book
+            .lock()
+            .unwrap()
+            .with_status(OrderStatus::Submitted)
+            .cloned()
+            .collect();
+        if submitted.is_empty() {
+            return;
+        }
+        let Some(reconcile_tx) = reconcile_tx else {
+            warn!(
+                "can't reconcile {} submitted orders without the bidder address, they will not be bid on",
+                submitted.len()
+            );
+
+            return;
+        };
+
+        for record in submitted {
+            let Some(bid) = record.bid else {
+                self.record(Some(&record.id), |book, id| {
+                    book.reset(id, "no bid was recorded")
+                });
+
+                continue;
+            };
+
+            let uncertain = UncertainBid {
+                order_id: Some(record.id),
+                bid,
+                tx: record.tx,
+            };
+            self.queue_uncertain(reconcile_tx, uncertain).await;
+        }
+    }
+
+    // Hands a bid to the reconciler. It counts against the wallet balance until it's resolved.
+    async fn queue_uncertain(&self, reconcile_tx: &Sender<UncertainBid>, uncertain: UncertainBid) {
+        self.outstanding.add(&uncertain.bid);
+        if let Err(err) = reconcile_tx.send(uncertain).await {
+            error!("failed to queue bid for reconciliation: {err:?}");
+        }
+    }
+
+    // Checks the wallet can pay for `bid` on top of the transaction fee and the bids sent that may
+    // yet land. Returns the bid to submit, cut down to what the wallet can cover if shrinking is
+    // enabled, or why it can't be submitted now.
+    async fn check_balance(
+        &self,
+        client: &mut Client,
+        bidder_address: &str,
+        bid: &Bid,
+    ) -> std::result::Result<Bid, String> {
+        let balance = match client.balances(bidder_address).await {
+            Ok(balances) => balances
+                .into_iter()
+                .find(|b| b.denom.is_usomm())
+                .map(|b| b.raw)
+                .unwrap_or_default(),
+            Err(err) => return Err(format!("failed to query the wallet balance: {err}")),
+        };
+        // bids that landed are already out of the balance, so only those still unresolved count
+        let wallet = WalletBalance {
+            balance,
+            outstanding: self.outstanding.usomm(),
+            fee: client.bid_tx_fee_usomm(),
+        };
+        debug!(
+            "usomm balance: {balance}, outstanding: {}, fee: {}, available: {}",
+            wallet.outstanding,
+            wallet.fee,
+            wallet.available()
+        );
+
+        if let Some(low) = self
+            .low_balance_warning_usomm
+            .and_then(|threshold| wallet.low_balance(bid, threshold))
+        {
+            warn!(
+                "low balance: {} left to bid with after this bid, below the warning threshold of {}",
+                low.remaining, low.threshold
+            );
+            // nobody listening is fine
+            let _ = self.events.send(EngineEvent::LowBalance(low));
+        }
+
+        let checked = wallet.fit(bid, self.shrink_bids_to_balance)?;
+        if &checked != bid {
+            info!(
+                "shrinking bid of {} to the {} the wallet has available",
+                bid.maximum_usomm_in, checked.maximum_usomm_in
+            );
+        }
+
+        Ok(checked)
+    }
+
+    // Applies a status change to the order a bid was for, if any. Failures are logged since the
+    // bid itself has already been dealt with.
+    fn record(&self, order_id: Option<&str>, f: impl FnOnce(&mut OrderBook, &str) -> Result<()>) {
+        let Some(id) = order_id else {
+            return;
+        };
+
+        let mut book = self.book.lock().unwrap();
+        match f(&mut book, id) {
+            Ok(()) => {
+                if let Some(record) = book.get(id) {
+                    info!("order {id} is now {:?}", record.status);
+                }
+            }
+            Err(err) => error!("failed to record status of order {id}: {err:?}"),
         }
     }
 
     pub async fn start(&mut self) -> Result<()> {
         info!("starting auction bot");
+
+        // check the node version before anything is signed
+        let mut client = Client::with_endpoints_and_version_check(
+            self.rpc_endpoint.clone(),
+            self.grpc_endpoint.clone(),
+            self.version_check,
+        )
+        .await?;
+        client.set_gas_price_usomm(self.gas_price_usomm)?;
+
+        self.verify_ibc_denoms(&mut client).await;
+
+        if self.verify_decimals {
+            self.verify_decimals(&mut client).await?;
+        }
+
+        match client.auction_parameters().await {
+            Ok(parameters) => {
+                info!("auction parameters: {parameters:?}");
+                if let Err(err) = parameters.validate() {
+                    warn!("auction parameters are out of bounds: {err:?}");
+                }
+
+                self.auction_parameters = Some(parameters);
+            }
+            Err(err) => error!("failed to query auction parameters: {err:?}"),
+        }
+
+        // bid submission service
+        let sender: Box<dyn BidSigner> = if let Some(signer) = self.signer.take() {
+            signer
+        } else if let Some(endpoint) = self.remote_signer.clone() {
+            let endpoint = endpoint
+                .parse()
+                .expect("failed to parse remote signer endpoint");
+            Box::new(RemoteSigner::new(endpoint))
+        } else if let Some(key_path) = self.signer_key_path.clone() {
+            Box::new(AccountInfo::from_pem(&key_path).expect("failed to load key"))
+        } else if let Ok(mnemonic) = std::env::var("SOMMELIER_AUCTION_MNEMONIC") {
+            Box::new(
+                AccountInfo::from_mnemonic(&mnemonic, "")
+                    .expect("failed to construct signer from mnemonic"),
+            )
+        } else {
+            panic!("no signer key provided and no mnemonic found in environment. either provide a key_path or remote_signer in the config or set SOMMELIER_AUCTION_MNEMONIC in the environment to a 24 word phrase.");
+        };
+        let bidder_address = match sender.address("somm").await {
+            Ok(address) => Some(address),
+            Err(err) => {
+                error!("failed to get bidder address, strategies will not see balances: {err:?}");
+
+                None
+            }
+        };
+
+        // submitted orders may go back to pending once they're reconciled
+        let mut strategies = std::mem::take(&mut self.strategies);
+        if self
+            .book
+            .lock()
+            .unwrap()
+            .orders()
+            .iter()
+            .any(|record| record.status.is_open())
+        {
+            strategies.push(Box::new(ThresholdStrategy::new(
+                self.book.clone(),
+                self.registry.clone(),
+                client.bid_tx_fee_usomm(),
+            )));
+        }
+        info!(
+            "running strategies: {:?}",
+            strategies.iter().map(|s| s.name()).collect::<Vec<&str>>()
+        );
+
         let mut watcher = Some(Watcher::new(
-            self.orders.clone(),
+            strategies,
+            self.registry.clone(),
+            self.auction_parameters.clone(),
             self.grpc_endpoint.clone(),
+            bidder_address.clone(),
+            self.budget.clone(),
         ));
 
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<Bid>(self.orders.len());
+        // uncertain bid reconciliation thread. without the bidder address there's no way to find
+        // our bids, so uncertain bids stay reserved and their orders stay submitted.
+        let (reconcile_tx, reconciler_handle) = match &bidder_address {
+            Some(address) => {
+                let (reconcile_tx, reconcile_rx) =
+                    tokio::sync::mpsc::channel::<UncertainBid>(BID_CHANNEL_CAPACITY);
+                let mut reconciler = Reconciler::new(
+                    self.book.clone(),
+                    self.budget.clone(),
+                    address.clone(),
+                    self.grpc_endpoint.clone(),
+                    self.minimum_bid_in_usomm(),
+                    self.outstanding.clone(),
+                );
+                let handle = tokio::spawn(async move {
+                    info!("starting reconciler thread");
+                    if let Err(err) = reconciler.run(reconcile_rx).await {
+                        error!("reconciler returned an error: {:?}", err);
+                    }
+                });
+
+                (Some(reconcile_tx), Some(handle))
+            }
+            None => (None, None),
+        };
+        self.reconcile_submitted_orders(reconcile_tx.as_ref()).await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<BidIntent>(BID_CHANNEL_CAPACITY);
 
         // auction monitoring thread
         let handle = tokio::spawn(async move {
@@ -87,34 +483,156 @@ impl OrderEngine {
             }
         });
 
-        // bid submission service
-        let sender = if let Some(key_path) = self.signer_key_path.clone() {
-            AccountInfo::from_pem(&key_path).expect("failed to load key")
-        } else if let Ok(mnemonic) = std::env::var("SOMMELIER_AUCTION_MNEMONIC") {
-            AccountInfo::from_mnemonic(&mnemonic, "")
-                .expect("failed to construct signer from mnemonic")
-        } else {
-            handle.abort();
-            panic!("no signer key provided and no mnemonic found in environment. either provide a key_path in the config or set SOMMELIER_AUCTION_MNEMONIC in the environment to a 24 word phrase.");
-        };
+        while let Some(BidIntent {
+            strategy,
+            order_id,
+            bid,
+        }) = rx.recv().await
+        {
+            if let Err(err) = self.budget.lock().unwrap().reserve(&bid) {
+                warn!("cancelling bid from {strategy} strategy: {err}");
+                self.record(order_id.as_deref(), |book, id| {
+                    book.cancel(id, Cancellation::Funds, &err)
+                });
+
+                continue;
+            }
+
+            let bid = match &bidder_address {
+                Some(address) => match self.check_balance(&mut client, address, &bid).await {
+                    Ok(checked) => {
+                        if checked != bid {
+                            let mut budget = self.budget.lock().unwrap();
+                            budget.release(&bid);
+                            if let Err(err) = budget.reserve(&checked) {
+                                error!("failed to reserve shrunk bid: {err}");
+                            }
+                        }
+
+                        checked
+                    }
+                    // the balance may well cover the bid later, once uncertain bids are resolved
+                    Err(reason) => {
+                        warn!("skipping bid from {strategy} strategy for now: {reason}");
+                        self.budget.lock().unwrap().release(&bid);
+                        self.record(order_id.as_deref(), |book, id| book.reset(id, &reason));
+
+                        continue;
+                    }
+                },
+                None => bid,
+            };
 
-        let mut client =
-            Client::with_endpoints(self.rpc_endpoint.clone(), self.grpc_endpoint.clone()).await?;
-        while let Some(bid) = rx.recv().await {
-            if let Err(err) = client.submit_bid(&sender, bid.clone()).await {
-                error!("error submitting bid: {:?}", err);
-                info!("this is likely a client timeout and the bid may be submitted successfully on chain.");
+            info!("submitting bid from {strategy} strategy: {bid:?}");
+            let signed = match client.sign_bid(sender.as_ref(), bid.clone()).await {
+                Ok(signed) => signed,
+                Err(err) => {
+                    self.budget.lock().unwrap().release(&bid);
+                    // a bid that breaks the auction's rules won't get better by retrying it. other
+                    // errors, such as a query timing out, may well clear up.
+                    if err.downcast_ref::<BidValidationError>().is_some() {
+                        warn!("bid was not submitted: {err}");
+                        self.record(order_id.as_deref(), |book, id| book.fail(id, &err));
+                    } else {
+                        error!("failed to sign bid, not submitting it: {err:?}");
+                        self.record(order_id.as_deref(), |book, id| {
+                            book.reset(id, format!("failed to sign bid: {err}"))
+                        });
+                    }
+
+                    continue;
+                }
+            };
+
+            // recorded before sending so a crash mid-submission can't lead to a second bid
+            if let Some(id) = &order_id {
+                if let Err(err) = self.book.lock().unwrap().submit(id, &bid, &signed.tx) {
+                    error!("failed to record bid for order {id}, not submitting it: {err:?}");
+                    self.budget.lock().unwrap().release(&bid);
+                    self.record(Some(id), |book, id| {
+                        book.cancel(id, Cancellation::Setup, &err)
+                    });
+
+                    continue;
+                }
             }
 
-            // to keep things simple and cautious we optimistically update the total_usomm_spent here.
-            // in reality the spent amount could be less.
-            self.total_usomm_spent += bid.maximum_usomm_in;
+            match client.broadcast_bid(&signed).await {
+                Ok(result) => {
+                    match BidView::try_from(&result) {
+                        Ok(view) => self
+                            .budget
+                            .lock()
+                            .unwrap()
+                            .settle(&bid, &view.total_usomm_paid),
+                        // to keep things cautious the full maximum stays reserved
+                        Err(err) => error!("failed to read bid result: {err:?}"),
+                    }
+
+                    let minimum_bid_in_usomm = self.minimum_bid_in_usomm();
+                    self.record(order_id.as_deref(), |book, id| {
+                        book.confirm(id, &result, minimum_bid_in_usomm)
+                    });
+                }
+                Err(err) => {
+                    error!("error submitting bid: {:?}", err);
+                    info!("this is likely a client timeout and the bid may be submitted successfully on chain.");
+
+                    match &reconcile_tx {
+                        Some(reconcile_tx) => {
+                            info!(
+                                "checking on chain whether bid transaction {} landed",
+                                signed.tx.hash
+                            );
+                            let uncertain = UncertainBid {
+                                order_id: order_id.clone(),
+                                bid: bid.clone(),
+                                tx: Some(signed.tx.clone()),
+                            };
+                            self.queue_uncertain(reconcile_tx, uncertain).await;
+                        }
+                        None => warn!("no bidder address to reconcile the bid with"),
+                    }
+                }
+            }
+
+            let budget = self.budget.lock().unwrap();
+            info!(
+                "usomm budget: {} reserved, {} committed, {} refunded",
+                budget.reserved(),
+                budget.committed(),
+                budget.refunded()
+            );
         }
 
         handle.abort();
 
+        // let outstanding uncertain bids resolve before shutting down
+        drop(reconcile_tx);
+        if let Some(reconciler_handle) = reconciler_handle {
+            info!("waiting for uncertain bids to be reconciled");
+            if let Err(err) = reconciler_handle.await {
+                error!("reconciler thread failed: {err:?}");
+            }
+        }
+
         info!("shutdown complete");
 
         Ok(())
     }
 }
+
+// Cancels the orders for `denom` that haven't been bid on
+fn cancel_pending_orders(book: &mut OrderBook, denom: &Denom, reason: &str) {
+    let ids: Vec<String> = book
+        .pending()
+        .filter(|record| record.order.fee_token() == denom)
+        .map(|record| record.id.clone())
+        .collect();
+
+    for id in ids {
+        if let Err(err) = book.cancel(&id, Cancellation::Setup, reason) {
+            error!("failed to record order {id} as cancelled: {err:?}");
+        }
+    }
+}
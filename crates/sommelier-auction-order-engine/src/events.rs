@@ -0,0 +1,21 @@
+use sommelier_auction::amount::TokenAmount;
+
+/// Something that happened while the engine was running that callers may want to act on.
+/// Subscribe with `OrderEngine::subscribe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineEvent {
+    /// A bid would leave the wallet with less uSOMM to bid with than `low_balance_warning_usomm`
+    LowBalance(LowBalance),
+}
+
+/// The wallet running low on uSOMM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowBalance {
+    /// The auction the bid is for
+    pub auction_id: u32,
+    /// The wallet's uSOMM balance before the bid
+    pub balance: TokenAmount,
+    /// What's left to bid with once the bid, its fee and the bids that may still land are paid for
+    pub remaining: TokenAmount,
+    pub threshold: TokenAmount,
+}
@@ -0,0 +1,154 @@
+//! Orders, bids and chain records shared by the unit tests
+use std::{collections::HashMap, path::PathBuf};
+
+use sommelier_auction::{
+    amount::TokenAmount,
+    bid::Bid,
+    client::BidTx,
+    cosmos_sdk_proto::cosmos::base::v1beta1::Coin,
+    denom::Denom,
+    view::{AuctionStatus, AuctionView, TokenPriceQuote, TokenPriceView},
+    BidResult,
+};
+
+use crate::{
+    budget::UsommBudget,
+    order::{Order, OrderEntry, UsdValueOrder},
+    strategy::MarketSnapshot,
+};
+
+/// A path under the temp dir unique to the test, with nothing at it yet
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("order-book-{name}-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    path
+}
+
+/// A USD value order for USDC
+pub(crate) fn entry(id: Option<&str>, maximum_usomm_in: u128) -> OrderEntry {
+    OrderEntry {
+        id: id.map(str::to_string),
+        ..Order::from(UsdValueOrder {
+            fee_token: Denom::USDC,
+            maximum_usomm_in: TokenAmount::usomm(maximum_usomm_in),
+            minimum_usd_value_out: 50.0,
+            min_profit_usd: None,
+            min_discount_bps: None,
+        })
+        .into()
+    }
+}
+
+pub(crate) fn tx(hash: &str) -> BidTx {
+    BidTx {
+        hash: hash.to_string(),
+        timeout_height: 200,
+    }
+}
+
+/// A bid on auction 1 for at least one base unit of USDC
+pub(crate) fn bid(maximum_usomm_in: u128) -> Bid {
+    bid_with(&Denom::USDC, maximum_usomm_in, 1)
+}
+
+/// A bid on auction 1
+pub(crate) fn bid_with(fee_token: &Denom, maximum_usomm_in: u128, minimum_tokens_out: u128) -> Bid {
+    Bid {
+        auction_id: 1,
+        fee_token: fee_token.clone(),
+        maximum_usomm_in: TokenAmount::usomm(maximum_usomm_in),
+        minimum_tokens_out: TokenAmount::new(fee_token.clone(), minimum_tokens_out),
+    }
+}
+
+/// The chain's record of `bid` as bid `id`, which got `fulfilled` base units for `paid` usomm at
+/// `unit_price` usomm each
+pub(crate) fn bid_result(
+    id: u64,
+    bid: &Bid,
+    fulfilled: u128,
+    unit_price: &str,
+    paid: u128,
+) -> BidResult {
+    let coin = |amount: u128, denom: &Denom| {
+        Some(Coin {
+            amount: amount.to_string(),
+            denom: denom.to_string(),
+        })
+    };
+
+    BidResult {
+        id,
+        auction_id: bid.auction_id,
+        bidder: "somm1bidder".to_string(),
+        max_bid_in_usomm: coin(bid.maximum_usomm_in.raw, &Denom::USOMM),
+        sale_token_minimum_amount: coin(bid.minimum_tokens_out.raw, &bid.fee_token),
+        total_fulfilled_sale_tokens: coin(fulfilled, &bid.fee_token),
+        sale_token_unit_price_in_usomm: unit_price.to_string(),
+        total_usomm_paid: coin(paid, &Denom::USOMM),
+        block_height: 100,
+    }
+}
+
+/// Active auction `id` selling `fee_token`, with `remaining` base units left at `unit_price` usomm
+/// each
+pub(crate) fn auction(
+    id: u32,
+    fee_token: &Denom,
+    remaining: u128,
+    unit_price: &str,
+) -> AuctionView {
+    let unit_price = unit_price.parse().unwrap();
+    let decrease_rate = "0.05".parse().unwrap();
+
+    AuctionView {
+        id,
+        status: AuctionStatus::Active,
+        starting_tokens_for_sale: TokenAmount::new(fee_token.clone(), remaining),
+        remaining_tokens_for_sale: TokenAmount::new(fee_token.clone(), remaining),
+        start_block: 100,
+        end_block: None,
+        initial_price_decrease_rate: decrease_rate,
+        current_price_decrease_rate: decrease_rate,
+        price_decrease_block_interval: 10,
+        initial_unit_price_in_usomm: unit_price,
+        current_unit_price_in_usomm: unit_price,
+        funding_module_account: String::new(),
+        proceeds_module_account: String::new(),
+    }
+}
+
+/// The market at height 150 with `active_auctions` and no prices
+pub(crate) fn snapshot(active_auctions: Vec<AuctionView>) -> MarketSnapshot {
+    MarketSnapshot {
+        height: 150,
+        active_auctions,
+        usd_prices: HashMap::new(),
+        somm_usd_price: None,
+        token_prices: Vec::new(),
+        auction_parameters: None,
+        balances: Vec::new(),
+        budget: UsommBudget::default(),
+    }
+}
+
+/// The auction module's price of `denom` as of height 150, `stale` or not
+pub(crate) fn quote(
+    denom: &Denom,
+    exponent: u8,
+    usd_price: &str,
+    is_stale: bool,
+) -> TokenPriceQuote {
+    TokenPriceQuote {
+        price: TokenPriceView {
+            denom: denom.clone(),
+            usd_price: usd_price.parse().unwrap(),
+            exponent,
+            last_updated_block: 140,
+        },
+        height: 150,
+        age_in_blocks: 10,
+        is_stale,
+    }
+}
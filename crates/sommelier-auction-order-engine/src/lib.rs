@@ -1,5 +1,13 @@
+pub mod balance;
+pub mod book;
+pub mod budget;
 pub mod config;
 pub mod engine;
+pub mod events;
+#[cfg(test)]
+mod fixtures;
 pub mod order;
-pub mod util;
+pub mod reconcile;
+pub mod strategy;
+pub mod threshold;
 pub mod watcher;
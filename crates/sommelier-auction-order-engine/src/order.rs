@@ -1,9 +1,501 @@
-use serde::{Deserialize, Serialize};
-use sommelier_auction::denom::Denom;
+use std::time::{Duration, SystemTime};
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Order {
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use sommelier_auction::{amount::TokenAmount, dec::Dec, denom::Denom, view::BidView};
+
+/// An order to buy a fee token. The `type` field picks the kind of order. Orders without one are
+/// [`UsdValueOrder`]s, so configs written before there were other kinds still load. An unknown
+/// `type` is an error rather than a reason to fall back to a [`UsdValueOrder`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Order {
+    UsdValue(UsdValueOrder),
+    LimitPrice(LimitPriceOrder),
+    OracleDiscount(OracleDiscountOrder),
+}
+
+/// An order as written in the config, with the id it's tracked by in the
+/// [`crate::book::OrderBook`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderEntry {
+    /// Identifies the order across restarts. Defaults to one derived from the order itself, so
+    /// orders keep their id when others are added, removed or reordered.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Unix time in seconds after which the order is no longer bid on
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// When a bid only partly fills the order, add a follow-up order for what's left
+    #[serde(default)]
+    pub rebid_partial_fills: bool,
+    #[serde(flatten)]
+    pub order: Order,
+}
+
+impl OrderEntry {
+    /// The configured id, or one derived from the order
+    pub fn id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| {
+            // FNV-1a, which unlike std's hasher is stable across Rust versions
+            let order =
+                serde_json::to_string(&self.order).unwrap_or_else(|_| format!("{:?}", self.order));
+            let hash = order.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            });
+
+            format!("{}-{hash:016x}", self.order.kind())
+        })
+    }
+
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+    }
+}
+
+impl From<Order> for OrderEntry {
+    fn from(order: Order) -> Self {
+        Self {
+            id: None,
+            expires_at: None,
+            rebid_partial_fills: false,
+            order,
+        }
+    }
+}
+
+/// Spends `maximum_usomm_in` once the tokens it buys are worth at least `minimum_usd_value_out`
+/// according to off-chain USD prices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsdValueOrder {
     pub fee_token: Denom,
-    pub maximum_usomm_in: u64,
+    #[serde(with = "sommelier_auction::amount::usomm")]
+    pub maximum_usomm_in: TokenAmount,
     pub minimum_usd_value_out: f64,
+    /// The least USD profit, after the usomm paid and fees are priced in USD, to bid at
+    #[serde(default)]
+    pub min_profit_usd: Option<f64>,
+    /// The least discount, in basis points of the USD value received, the usomm paid and fees
+    /// must come in under to bid
+    #[serde(default)]
+    pub min_discount_bps: Option<u32>,
+}
+
+impl UsdValueOrder {
+    /// Whether the order only bids when the trade is profitable in USD terms
+    pub fn requires_profit(&self) -> bool {
+        self.min_profit_usd.is_some() || self.min_discount_bps.is_some()
+    }
+}
+
+/// Buys `quantity` base units of the fee token once the auction's unit price falls to
+/// `max_usomm_per_token` usomm per whole token or lower. Needs no off-chain prices.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitPriceOrder {
+    pub fee_token: Denom,
+    /// The most usomm to pay for one whole token
+    #[serde(with = "sommelier_auction::amount::raw_string")]
+    pub max_usomm_per_token: u128,
+    /// The amount of the fee token to buy, in base units
+    #[serde(with = "sommelier_auction::amount::raw_string")]
+    pub quantity: u128,
+}
+
+impl LimitPriceOrder {
+    /// The limit as a price in usomm per base unit, comparable to an auction's unit price
+    pub fn max_unit_price_in_usomm(&self, decimals: u8) -> Option<Dec> {
+        let scale = 10i128.checked_pow(Dec::PRECISION.checked_sub(decimals as u32)?)?;
+        let atomics = i128::try_from(self.max_usomm_per_token)
+            .ok()?
+            .checked_mul(scale)?;
+
+        Some(Dec::from_atomics(atomics))
+    }
+}
+
+/// Spends `maximum_usomm_in` once the auction's unit price is at least `min_discount_bps` basis
+/// points below the fair price implied by the auction module's own token prices for the fee token
+/// and usomm. Needs no off-chain prices, and doesn't bid while either on-chain price is stale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OracleDiscountOrder {
+    pub fee_token: Denom,
+    #[serde(with = "sommelier_auction::amount::usomm")]
+    pub maximum_usomm_in: TokenAmount,
+    pub min_discount_bps: u32,
+}
+
+impl Order {
+    pub fn fee_token(&self) -> &Denom {
+        match self {
+            Order::UsdValue(order) => &order.fee_token,
+            Order::LimitPrice(order) => &order.fee_token,
+            Order::OracleDiscount(order) => &order.fee_token,
+        }
+    }
+
+    /// The most usomm a bid for the order can spend. `None` if it can't be worked out.
+    pub fn maximum_usomm_in(&self, decimals: u8) -> Option<TokenAmount> {
+        match self {
+            Order::UsdValue(order) => Some(order.maximum_usomm_in.clone()),
+            Order::LimitPrice(order) => order
+                .max_unit_price_in_usomm(decimals)?
+                .mul_int_ceil(order.quantity)
+                .map(TokenAmount::usomm),
+            Order::OracleDiscount(order) => Some(order.maximum_usomm_in.clone()),
+        }
+    }
+
+    /// The order's `type`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Order::UsdValue(_) => "usd_value",
+            Order::LimitPrice(_) => "limit_price",
+            Order::OracleDiscount(_) => "oracle_discount",
+        }
+    }
+
+    /// Whether the order needs an off-chain USD price for its fee token
+    pub fn needs_usd_price(&self) -> bool {
+        matches!(self, Order::UsdValue(_))
+    }
+
+    /// Whether `fill` bought less than the order's bid could have at the price it was filled at.
+    /// Orders that spend a usomm amount compare the tokens received with what the whole amount
+    /// buys at that price, so usomm left over from rounding doesn't count.
+    pub fn is_partially_filled_by(&self, fill: &BidView) -> bool {
+        let fulfilled = fill.total_fulfilled_sale_tokens.raw;
+        let unit_price = fill.sale_token_unit_price_in_usomm;
+
+        match self {
+            Order::UsdValue(UsdValueOrder {
+                maximum_usomm_in, ..
+            })
+            | Order::OracleDiscount(OracleDiscountOrder {
+                maximum_usomm_in, ..
+            }) => unit_price
+                .div_int_floor(maximum_usomm_in.raw)
+                .is_some_and(|affordable| fulfilled < affordable),
+            Order::LimitPrice(order) => fulfilled < order.quantity,
+        }
+    }
+
+    /// The order for what a bid filled by `fill` left unbought, with the same price limits, or
+    /// `None` if the bid filled the whole order or what's left is too small to bid on: it must buy
+    /// at least one base unit at the fill's price and come to at least `minimum_bid_in_usomm`.
+    /// USD value orders keep their minimum value and profit in proportion to the usomm left to
+    /// spend.
+    pub fn remainder(&self, fill: &BidView, minimum_bid_in_usomm: u64) -> Option<Order> {
+        if !self.is_partially_filled_by(fill) {
+            return None;
+        }
+
+        let unit_price = fill.sale_token_unit_price_in_usomm;
+        let biddable = |usomm: u128| {
+            usomm >= minimum_bid_in_usomm as u128
+                && unit_price
+                    .div_int_floor(usomm)
+                    .is_some_and(|tokens| tokens >= 1)
+        };
+
+        match self {
+            Order::UsdValue(order) => {
+                let maximum = order.maximum_usomm_in.raw;
+                let remaining = maximum.checked_sub(fill.total_usomm_paid.raw)?;
+                if !biddable(remaining) {
+                    return None;
+                }
+
+                let share = remaining as f64 / maximum as f64;
+                Some(Order::UsdValue(UsdValueOrder {
+                    maximum_usomm_in: TokenAmount::usomm(remaining),
+                    minimum_usd_value_out: order.minimum_usd_value_out * share,
+                    min_profit_usd: order.min_profit_usd.map(|p| p * share),
+                    ..order.clone()
+                }))
+            }
+            Order::LimitPrice(order) => {
+                let remaining = order
+                    .quantity
+                    .checked_sub(fill.total_fulfilled_sale_tokens.raw)?;
+                if !unit_price.mul_int_ceil(remaining).is_some_and(biddable) {
+                    return None;
+                }
+
+                Some(Order::LimitPrice(LimitPriceOrder {
+                    quantity: remaining,
+                    ..order.clone()
+                }))
+            }
+            Order::OracleDiscount(order) => {
+                let remaining = order
+                    .maximum_usomm_in
+                    .raw
+                    .checked_sub(fill.total_usomm_paid.raw)?;
+                if !biddable(remaining) {
+                    return None;
+                }
+
+                Some(Order::OracleDiscount(OracleDiscountOrder {
+                    maximum_usomm_in: TokenAmount::usomm(remaining),
+                    ..order.clone()
+                }))
+            }
+        }
+    }
+}
+
+impl From<UsdValueOrder> for Order {
+    fn from(order: UsdValueOrder) -> Self {
+        Order::UsdValue(order)
+    }
+}
+
+impl From<LimitPriceOrder> for Order {
+    fn from(order: LimitPriceOrder) -> Self {
+        Order::LimitPrice(order)
+    }
+}
+
+impl From<OracleDiscountOrder> for Order {
+    fn from(order: OracleDiscountOrder) -> Self {
+        Order::OracleDiscount(order)
+    }
+}
+
+// Orders are read through a map so the presence of `type`, not whether the fields happen to fit a
+// [`UsdValueOrder`], decides how they're parsed
+impl<'de> Deserialize<'de> for Order {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = serde_json::Map::deserialize(deserializer)?;
+        let tagged = fields.contains_key("type");
+        let fields = serde_json::Value::Object(fields);
+
+        let order = if tagged {
+            TaggedOrder::deserialize(fields).map(Order::from)
+        } else {
+            UsdValueOrder::deserialize(fields).map(Order::UsdValue)
+        };
+
+        order.map_err(D::Error::custom)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TaggedOrder {
+    UsdValue(UsdValueOrder),
+    LimitPrice(LimitPriceOrder),
+    OracleDiscount(OracleDiscountOrder),
+}
+
+impl From<TaggedOrder> for Order {
+    fn from(order: TaggedOrder) -> Self {
+        match order {
+            TaggedOrder::UsdValue(order) => Order::UsdValue(order),
+            TaggedOrder::LimitPrice(order) => Order::LimitPrice(order),
+            TaggedOrder::OracleDiscount(order) => Order::OracleDiscount(order),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::fixtures::{bid, bid_result};
+
+    fn usd_value_order() -> UsdValueOrder {
+        UsdValueOrder {
+            fee_token: Denom::USDC,
+            maximum_usomm_in: TokenAmount::usomm(25_000_000),
+            minimum_usd_value_out: 50.0,
+            min_profit_usd: None,
+            min_discount_bps: None,
+        }
+    }
+
+    // how a bid of 1000 usomm for USDC filled
+    fn fill(fulfilled: u128, unit_price: &str, paid: u128) -> BidView {
+        BidView::try_from(bid_result(1, &bid(1000), fulfilled, unit_price, paid)).unwrap()
+    }
+
+    fn limit_price_order(quantity: u128) -> Order {
+        Order::LimitPrice(LimitPriceOrder {
+            fee_token: Denom::USDC,
+            max_usomm_per_token: 2_000_000,
+            quantity,
+        })
+    }
+
+    #[test]
+    fn test_rounding_dust_is_not_a_partial_fill() {
+        // 1000 usomm buys 333 units at 3 usomm, leaving 1 usomm unspent
+        let order = Order::from(UsdValueOrder {
+            maximum_usomm_in: TokenAmount::usomm(1000),
+            ..usd_value_order()
+        });
+        let complete = fill(333, "3.0", 999);
+
+        assert!(!order.is_partially_filled_by(&complete));
+        assert_eq!(order.remainder(&complete, 0), None);
+
+        let oracle = Order::from(OracleDiscountOrder {
+            fee_token: Denom::USDC,
+            maximum_usomm_in: TokenAmount::usomm(1000),
+            min_discount_bps: 100,
+        });
+        assert!(!oracle.is_partially_filled_by(&complete));
+        assert_eq!(oracle.remainder(&complete, 0), None);
+    }
+
+    #[test]
+    fn test_usd_value_remainder() {
+        let order = Order::from(UsdValueOrder {
+            maximum_usomm_in: TokenAmount::usomm(1000),
+            min_profit_usd: Some(10.0),
+            ..usd_value_order()
+        });
+        let partial = fill(100, "3.0", 300);
+
+        assert!(order.is_partially_filled_by(&partial));
+        assert_eq!(
+            order.remainder(&partial, 700),
+            Some(Order::from(UsdValueOrder {
+                maximum_usomm_in: TokenAmount::usomm(700),
+                minimum_usd_value_out: 35.0,
+                min_profit_usd: Some(7.0),
+                ..usd_value_order()
+            }))
+        );
+
+        // below the auction's minimum bid
+        assert_eq!(order.remainder(&partial, 701), None);
+    }
+
+    #[test]
+    fn test_remainder_must_buy_a_base_unit() {
+        let order = Order::from(OracleDiscountOrder {
+            fee_token: Denom::USDC,
+            maximum_usomm_in: TokenAmount::usomm(1000),
+            min_discount_bps: 100,
+        });
+
+        // one unit short, but the 2 usomm left can't buy it
+        let short = fill(332, "3.0", 998);
+        assert!(order.is_partially_filled_by(&short));
+        assert_eq!(order.remainder(&short, 0), None);
+
+        let partial = fill(330, "3.0", 990);
+        assert_eq!(
+            order.remainder(&partial, 0),
+            Some(Order::from(OracleDiscountOrder {
+                fee_token: Denom::USDC,
+                maximum_usomm_in: TokenAmount::usomm(10),
+                min_discount_bps: 100,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_limit_price_remainder() {
+        let order = limit_price_order(100);
+
+        let complete = fill(100, "2.0", 200);
+        assert!(!order.is_partially_filled_by(&complete));
+        assert_eq!(order.remainder(&complete, 0), None);
+
+        // the 40 units left cost 80 usomm at the fill's price
+        let partial = fill(60, "2.0", 120);
+        assert!(order.is_partially_filled_by(&partial));
+        assert_eq!(order.remainder(&partial, 80), Some(limit_price_order(40)));
+        assert_eq!(order.remainder(&partial, 81), None);
+    }
+
+    #[test]
+    fn test_untagged_order_loads_as_usd_value() {
+        let entry: OrderEntry = serde_json::from_value(json!({
+            "id": "usdc",
+            "fee_token": Denom::USDC.to_string(),
+            "maximum_usomm_in": 25_000_000,
+            "minimum_usd_value_out": 50.0,
+        }))
+        .unwrap();
+
+        assert_eq!(entry.id.as_deref(), Some("usdc"));
+        assert_eq!(entry.order, Order::UsdValue(usd_value_order()));
+    }
+
+    #[test]
+    fn test_tagged_orders_load() {
+        let order: Order = serde_json::from_value(json!({
+            "type": "usd_value",
+            "fee_token": Denom::USDC.to_string(),
+            "maximum_usomm_in": 25_000_000,
+            "minimum_usd_value_out": 50.0,
+        }))
+        .unwrap();
+        assert_eq!(order, Order::UsdValue(usd_value_order()));
+
+        let entry: OrderEntry = serde_json::from_value(json!({
+            "type": "limit_price",
+            "expires_at": 1_700_000_000,
+            "fee_token": Denom::USDC.to_string(),
+            "max_usomm_per_token": "2000000",
+            "quantity": "1000000",
+        }))
+        .unwrap();
+        assert_eq!(entry.expires_at, Some(1_700_000_000));
+        assert_eq!(
+            entry.order,
+            Order::LimitPrice(LimitPriceOrder {
+                fee_token: Denom::USDC,
+                max_usomm_per_token: 2_000_000,
+                quantity: 1_000_000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let order = Order::OracleDiscount(OracleDiscountOrder {
+            fee_token: Denom::USDC,
+            maximum_usomm_in: TokenAmount::usomm(25_000_000),
+            min_discount_bps: 150,
+        });
+        let json = serde_json::to_value(&order).unwrap();
+
+        assert_eq!(json["type"], "oracle_discount");
+        assert_eq!(serde_json::from_value::<Order>(json).unwrap(), order);
+    }
+
+    #[test]
+    fn test_misspelled_type_is_rejected() {
+        // fits a usd value order apart from the type, so an untagged fallback would take it
+        let err = serde_json::from_value::<OrderEntry>(json!({
+            "type": "limt_price",
+            "fee_token": Denom::USDC.to_string(),
+            "maximum_usomm_in": 25_000_000,
+            "minimum_usd_value_out": 50.0,
+        }))
+        .unwrap_err();
+
+        assert!(
+            err.to_string().contains("unknown variant `limt_price`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_tagged_order_with_missing_fields_is_rejected() {
+        let err = serde_json::from_value::<Order>(json!({
+            "type": "oracle_discount",
+            "fee_token": Denom::USDC.to_string(),
+            "maximum_usomm_in": 25_000_000,
+            "minimum_usd_value_out": 50.0,
+        }))
+        .unwrap_err();
+
+        assert!(err.to_string().contains("min_discount_bps"), "{err}");
+    }
 }
@@ -0,0 +1,447 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use eyre::Result;
+use sommelier_auction::{
+    bid::Bid,
+    client::{BidTx, BidTxStatus, Client},
+    version::VersionCheck,
+    view::BidView,
+    BidResult,
+};
+use tokio::sync::mpsc::Receiver;
+use tracing::{error, info, warn};
+
+use crate::{book::OrderBook, budget::UsommBudget};
+
+/// What became of a bid whose outcome wasn't known
+#[derive(Debug, Clone)]
+pub enum BidOutcome {
+    /// The bid is on chain
+    Landed(Box<BidResult>),
+    /// The bid was never placed
+    Lost,
+}
+
+/// Settles an uncertain bid's budget reservation with what it turned out to pay and records the
+/// outcome on its order. A bid that landed confirms its order, one that didn't puts it back to
+/// pending to be bid on again. `minimum_bid_in_usomm` decides whether what a partial fill left is
+/// enough to rebid.
+pub fn apply_outcome(
+    book: &mut OrderBook,
+    budget: &mut UsommBudget,
+    order_id: Option<&str>,
+    bid: &Bid,
+    outcome: &BidOutcome,
+    minimum_bid_in_usomm: u64,
+) -> Result<()> {
+    match outcome {
+        BidOutcome::Landed(result) => {
+            match BidView::try_from(result.as_ref()) {
+                Ok(view) => budget.settle(bid, &view.total_usomm_paid),
+                // to keep things cautious the full maximum stays reserved
+                Err(err) => error!("failed to read bid result: {err:?}"),
+            }
+
+            match order_id {
+                Some(id) => book.confirm(id, result, minimum_bid_in_usomm),
+                None => Ok(()),
+            }
+        }
+        BidOutcome::Lost => {
+            budget.release(bid);
+
+            match order_id {
+                Some(id) => book.reset(id, "bid never landed on chain"),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Works out an uncertain bid's outcome from what the chain knows of its transaction as of
+/// `height`. `None` while the transaction can still be included.
+pub fn resolve(status: BidTxStatus, tx: &BidTx, height: u64) -> Option<BidOutcome> {
+    match status {
+        BidTxStatus::Placed(result) => Some(BidOutcome::Landed(result)),
+        BidTxStatus::Failed { code, log } => {
+            warn!("bid transaction {} failed with code {code}: {log}", tx.hash);
+
+            Some(BidOutcome::Lost)
+        }
+        BidTxStatus::NotFound if height > tx.timeout_height => Some(BidOutcome::Lost),
+        BidTxStatus::NotFound => None,
+    }
+}
+
+// how often uncertain bids are looked for on chain
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A bid whose submission errored without saying whether it was placed, such as when the client
+/// timed out waiting for the transaction to be committed
+#[derive(Debug, Clone)]
+pub struct UncertainBid {
+    /// Id of the order the bid was for, if any
+    pub order_id: Option<String>,
+    pub bid: Bid,
+    /// The transaction the bid was sent in. Unknown for bids recorded by earlier versions, which
+    /// can only be matched against the auction's bids.
+    pub tx: Option<BidTx>,
+}
+
+/// The bids handed to the reconciler that it hasn't resolved yet. They may still take uSOMM out of
+/// the wallet, unlike bids known to have landed, which the wallet balance already reflects.
+#[derive(Debug, Clone, Default)]
+pub struct OutstandingBids(Arc<Mutex<Vec<Bid>>>);
+
+impl OutstandingBids {
+    pub fn add(&self, bid: &Bid) {
+        self.0.lock().unwrap().push(bid.clone());
+    }
+
+    pub fn remove(&self, bid: &Bid) {
+        let mut bids = self.0.lock().unwrap();
+        if let Some(index) = bids.iter().position(|b| b == bid) {
+            bids.remove(index);
+        }
+    }
+
+    /// The most uSOMM the outstanding bids could still take
+    pub fn usomm(&self) -> u128 {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|bid| bid.maximum_usomm_in.raw)
+            .sum()
+    }
+}
+
+/// Works out what happened to uncertain bids by looking up the transactions they were sent in. A
+/// bid that landed settles its budget reservation with what it really paid and confirms its
+/// order. A bid whose transaction failed, or timed out without being included, is released from
+/// the budget and its order goes back to pending to be bid on again.
+pub struct Reconciler {
+    book: Arc<Mutex<OrderBook>>,
+    budget: Arc<Mutex<UsommBudget>>,
+    bidder_address: String,
+    grpc_endpoint: String,
+    minimum_bid_in_usomm: u64,
+    // shared with the engine, which adds bids as it queues them
+    outstanding: OutstandingBids,
+    uncertain: Vec<UncertainBid>,
+}
+
+impl Reconciler {
+    pub fn new(
+        book: Arc<Mutex<OrderBook>>,
+        budget: Arc<Mutex<UsommBudget>>,
+        bidder_address: String,
+        grpc_endpoint: String,
+        minimum_bid_in_usomm: u64,
+        outstanding: OutstandingBids,
+    ) -> Self {
+        Self {
+            book,
+            budget,
+            bidder_address,
+            grpc_endpoint,
+            minimum_bid_in_usomm,
+            outstanding,
+            uncertain: Vec::new(),
+        }
+    }
+
+    /// Reconciles the uncertain bids received on `rx` until the channel closes and every bid has
+    /// been resolved
+    pub async fn run(&mut self, mut rx: Receiver<UncertainBid>) -> Result<()> {
+        // the engine has already checked the node version
+        let mut client = Client::with_endpoints_and_version_check(
+            "".to_string(),
+            self.grpc_endpoint.clone(),
+            VersionCheck::Skip,
+        )
+        .await?;
+        let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+        let mut receiving = true;
+
+        loop {
+            tokio::select! {
+                received = rx.recv(), if receiving => match received {
+                    Some(uncertain) => {
+                        info!("reconciling uncertain bid: {:?}", uncertain.bid);
+                        self.uncertain.push(uncertain);
+                    }
+                    None => receiving = false,
+                },
+                _ = interval.tick(), if !self.uncertain.is_empty() => {
+                    self.reconcile(&mut client).await;
+                }
+            }
+
+            if !receiving && self.uncertain.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn reconcile(&mut self, client: &mut Client) {
+        // the height is taken before looking transactions up, so a transaction not found is
+        // known not to have been included up to it
+        let height = match client.latest_block_height().await {
+            Ok(height) => height,
+            Err(err) => {
+                error!("failed to query the latest block height: {err:?}");
+
+                return;
+            }
+        };
+
+        let mut unresolved = Vec::new();
+        for uncertain in std::mem::take(&mut self.uncertain) {
+            match self.look_up(client, &uncertain, height).await {
+                Ok(Some(outcome)) => self.apply(&uncertain, &outcome),
+                Ok(None) => unresolved.push(uncertain),
+                Err(err) => {
+                    error!(
+                        "failed to look up bid for auction {}: {err:?}",
+                        uncertain.bid.auction_id
+                    );
+                    unresolved.push(uncertain);
+                }
+            }
+        }
+
+        self.uncertain = unresolved;
+    }
+
+    async fn look_up(
+        &self,
+        client: &mut Client,
+        uncertain: &UncertainBid,
+        height: u64,
+    ) -> Result<Option<BidOutcome>> {
+        let bid = &uncertain.bid;
+        if let Some(tx) = &uncertain.tx {
+            let status = client.bid_tx_status(bid, tx, &self.bidder_address).await?;
+
+            return Ok(resolve(status, tx, height));
+        }
+
+        // without the transaction, a matching bid that isn't already some other order's is the
+        // best there is
+        let known = self.book.lock().unwrap().linked_bid_ids(bid.auction_id);
+        let found = client.find_bid(bid, &self.bidder_address, &known).await?;
+
+        Ok(Some(match found {
+            Some(result) => BidOutcome::Landed(Box::new(result)),
+            None => BidOutcome::Lost,
+        }))
+    }
+
+    fn apply(&self, uncertain: &UncertainBid, outcome: &BidOutcome) {
+        match outcome {
+            BidOutcome::Landed(result) => info!("uncertain bid landed as bid {}", result.id),
+            BidOutcome::Lost => warn!(
+                "uncertain bid never landed on chain, releasing it: {:?}",
+                uncertain.bid
+            ),
+        }
+
+        let mut book = self.book.lock().unwrap();
+        let mut budget = self.budget.lock().unwrap();
+        let order_id = uncertain.order_id.as_deref();
+
+        if let Err(err) = apply_outcome(
+            &mut book,
+            &mut budget,
+            order_id,
+            &uncertain.bid,
+            outcome,
+            self.minimum_bid_in_usomm,
+        ) {
+            error!(
+                "failed to record outcome of bid for order {}: {err:?}",
+                order_id.unwrap_or_default()
+            );
+        }
+        self.outstanding.remove(&uncertain.bid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sommelier_auction::denom::Denom;
+
+    use super::*;
+    use crate::{
+        book::OrderStatus,
+        fixtures::{bid, bid_result, entry, temp_path, tx},
+    };
+
+    fn restart(
+        path: &std::path::Path,
+        entries: &[crate::order::OrderEntry],
+    ) -> (OrderBook, UsommBudget) {
+        let book = OrderBook::open(Some(path.to_path_buf()), entries).unwrap();
+        let mut budget = UsommBudget::new(Some(10_000), HashMap::new());
+        budget.restore(book.orders());
+
+        (book, budget)
+    }
+
+    #[test]
+    fn test_restart_and_reconcile() {
+        let path = temp_path("restart");
+        let entries = [
+            entry(Some("filled"), 1000),
+            entry(Some("landed"), 2000),
+            entry(Some("lost"), 3000),
+        ];
+
+        // bids recorded as submitted, then a crash before their outcome was known
+        let (mut book, mut budget) = restart(&path, &entries);
+        for (id, max) in [("filled", 1000), ("landed", 2000), ("lost", 3000)] {
+            budget.reserve(&bid(max)).unwrap();
+            book.trigger(id, &bid(max)).unwrap();
+            book.submit(id, &bid(max), &tx(id)).unwrap();
+        }
+        apply_outcome(
+            &mut book,
+            &mut budget,
+            Some("filled"),
+            &bid(1000),
+            &BidOutcome::Landed(Box::new(bid_result(1, &bid(1000), 1000, "1.0", 1000))),
+            0,
+        )
+        .unwrap();
+        drop(book);
+
+        // the restarted budget has what was paid and what may have been
+        let (mut book, mut budget) = restart(&path, &entries);
+        assert_eq!(budget.committed().raw, 1000);
+        assert_eq!(budget.reserved().raw, 5000);
+        assert_eq!(budget.available(&Denom::USDC).unwrap().raw, 4000);
+        assert_eq!(book.get("landed").unwrap().status, OrderStatus::Submitted);
+
+        apply_outcome(
+            &mut book,
+            &mut budget,
+            Some("landed"),
+            &bid(2000),
+            &BidOutcome::Landed(Box::new(bid_result(2, &bid(2000), 2000, "1.0", 2000))),
+            0,
+        )
+        .unwrap();
+        apply_outcome(
+            &mut book,
+            &mut budget,
+            Some("lost"),
+            &bid(3000),
+            &BidOutcome::Lost,
+            0,
+        )
+        .unwrap();
+        assert_eq!(budget.committed().raw, 3000);
+        assert_eq!(budget.reserved().raw, 0);
+        assert_eq!(book.get("landed").unwrap().status, OrderStatus::Confirmed);
+        assert_eq!(book.get("lost").unwrap().status, OrderStatus::Pending);
+        drop(book);
+
+        let (book, budget) = restart(&path, &entries);
+        assert_eq!(budget.committed().raw, 3000);
+        assert_eq!(budget.reserved().raw, 0);
+        assert_eq!(book.get("lost").unwrap().status, OrderStatus::Pending);
+        assert_eq!(book.get("lost").unwrap().bid, None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_resolve() {
+        let tx = tx("A");
+        let placed = || BidTxStatus::Placed(Box::new(bid_result(1, &bid(1000), 1000, "1.0", 1000)));
+        let failed = || BidTxStatus::Failed {
+            code: 5,
+            log: "insufficient funds".to_string(),
+        };
+
+        assert!(matches!(
+            resolve(placed(), &tx, 150),
+            Some(BidOutcome::Landed(result)) if result.id == 1
+        ));
+        assert!(matches!(
+            resolve(placed(), &tx, 250),
+            Some(BidOutcome::Landed(_))
+        ));
+        assert!(matches!(
+            resolve(failed(), &tx, 150),
+            Some(BidOutcome::Lost)
+        ));
+
+        // a transaction not found can still be included up to and at its timeout height
+        assert!(resolve(BidTxStatus::NotFound, &tx, 150).is_none());
+        assert!(resolve(BidTxStatus::NotFound, &tx, 200).is_none());
+        assert!(matches!(
+            resolve(BidTxStatus::NotFound, &tx, 201),
+            Some(BidOutcome::Lost)
+        ));
+    }
+
+    #[test]
+    fn test_linked_bids_are_skipped() {
+        let entries = [entry(Some("a"), 1000), entry(Some("b"), 1000)];
+        let mut book = OrderBook::open(None, &entries).unwrap();
+        let mut budget = UsommBudget::default();
+        for id in ["a", "b"] {
+            book.trigger(id, &bid(1000)).unwrap();
+            book.submit(id, &bid(1000), &tx(id)).unwrap();
+        }
+        assert!(book.linked_bid_ids(1).is_empty());
+
+        let outcome = BidOutcome::Landed(Box::new(bid_result(7, &bid(1000), 1000, "1.0", 1000)));
+        apply_outcome(&mut book, &mut budget, Some("a"), &bid(1000), &outcome, 0).unwrap();
+
+        assert_eq!(book.linked_bid_ids(1), [7]);
+        assert!(book.linked_bid_ids(2).is_empty());
+    }
+
+    #[test]
+    fn test_landed_bid_refunds_unpaid() {
+        let mut book = OrderBook::open(None, &[entry(Some("a"), 2000)]).unwrap();
+        let mut budget = UsommBudget::new(Some(10_000), HashMap::new());
+        budget.reserve(&bid(2000)).unwrap();
+        book.trigger("a", &bid(2000)).unwrap();
+        book.submit("a", &bid(2000), &tx("a")).unwrap();
+
+        let outcome = BidOutcome::Landed(Box::new(bid_result(1, &bid(2000), 1500, "1.0", 1500)));
+        apply_outcome(&mut book, &mut budget, Some("a"), &bid(2000), &outcome, 0).unwrap();
+
+        assert_eq!(budget.committed().raw, 1500);
+        assert_eq!(budget.refunded().raw, 500);
+        assert_eq!(book.get("a").unwrap().result.as_ref().unwrap().id, 1);
+    }
+
+    #[test]
+    fn test_outstanding_bids() {
+        let outstanding = OutstandingBids::default();
+        let shared = outstanding.clone();
+        outstanding.add(&bid(1000));
+        outstanding.add(&bid(1000));
+        outstanding.add(&bid(2000));
+        assert_eq!(shared.usomm(), 4000);
+
+        // identical bids are resolved one at a time
+        shared.remove(&bid(1000));
+        assert_eq!(outstanding.usomm(), 3000);
+        shared.remove(&bid(3000));
+        assert_eq!(outstanding.usomm(), 3000);
+        shared.remove(&bid(1000));
+        shared.remove(&bid(2000));
+        assert_eq!(outstanding.usomm(), 0);
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use sommelier_auction::{
+    amount::TokenAmount,
+    bid::Bid,
+    denom::Denom,
+    parameters::AuctionParameters,
+    view::{AuctionView, TokenPriceQuote},
+};
+
+use crate::budget::UsommBudget;
+
+/// The market state strategies make decisions on. The [`crate::watcher::Watcher`] builds a new
+/// snapshot roughly every block.
+#[derive(Debug, Clone, Default)]
+pub struct MarketSnapshot {
+    /// Height of the latest block
+    pub height: u64,
+    pub active_auctions: Vec<AuctionView>,
+    /// Off-chain USD prices of one whole token, for the denoms strategies asked for
+    pub usd_prices: HashMap<Denom, f64>,
+    /// Off-chain USD price of one SOMM
+    pub somm_usd_price: Option<f64>,
+    /// The auction module's token prices and their freshness
+    pub token_prices: Vec<TokenPriceQuote>,
+    pub auction_parameters: Option<AuctionParameters>,
+    /// Balances of the bidding account, empty if its address isn't known
+    pub balances: Vec<TokenAmount>,
+    /// The engine's usomm budget. Bids that don't fit are cancelled by the engine.
+    pub budget: UsommBudget,
+}
+
+impl MarketSnapshot {
+    /// The off-chain USD price of one whole token of `denom`
+    pub fn usd_price(&self, denom: &Denom) -> Option<f64> {
+        self.usd_prices.get(denom).copied()
+    }
+
+    /// The auction module's price of `denom`
+    pub fn token_price(&self, denom: &Denom) -> Option<&TokenPriceQuote> {
+        self.token_prices.iter().find(|p| &p.price.denom == denom)
+    }
+
+    /// The bidding account's balance of `denom`
+    pub fn balance(&self, denom: &Denom) -> TokenAmount {
+        self.balances
+            .iter()
+            .find(|b| &b.denom == denom)
+            .cloned()
+            .unwrap_or_else(|| TokenAmount::zero(denom.clone()))
+    }
+}
+
+/// A bid a strategy wants submitted
+#[derive(Debug, Clone)]
+pub struct BidIntent {
+    /// Name of the strategy that produced the bid
+    pub strategy: String,
+    /// Id of the configured order the bid is for, so the engine can record its outcome in the
+    /// order book. Strategies with their own orders leave it unset.
+    pub order_id: Option<String>,
+    pub bid: Bid,
+}
+
+/// Bidding logic the [`crate::engine::OrderEngine`] can run. Each strategy is shown every market
+/// snapshot and returns the bids it wants placed, which the engine relays to its bid submission
+/// service.
+pub trait Strategy: Send {
+    /// A name for the strategy, used in logs and to attribute bids
+    fn name(&self) -> &str;
+
+    /// The denoms the strategy needs off-chain USD prices for
+    fn price_denoms(&self) -> Vec<Denom>;
+
+    /// Decides which bids to place given the latest market state
+    fn evaluate(&mut self, snapshot: &MarketSnapshot) -> Vec<BidIntent>;
+
+    /// Whether the strategy has nothing left to do. The watcher shuts down once every strategy
+    /// is finished.
+    fn is_finished(&self) -> bool {
+        false
+    }
+}
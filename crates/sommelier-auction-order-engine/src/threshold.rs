@@ -0,0 +1,768 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use sommelier_auction::{
+    amount::TokenAmount,
+    bid::Bid,
+    dec::Dec,
+    denom::Denom,
+    registry::DenomRegistry,
+    simulation::{simulate_bid_at_price, Fill, SimulatedFill},
+    view::AuctionView,
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    book::{Cancellation, OrderBook, OrderRecord},
+    order::{LimitPriceOrder, OracleDiscountOrder, Order, UsdValueOrder},
+    strategy::{BidIntent, MarketSnapshot, Strategy},
+};
+
+// usomm is the base unit of SOMM
+const USOMM_DECIMALS: u8 = 6;
+
+/// Bids on each pending order in the [`OrderBook`] once its trigger is met: for
+/// [`UsdValueOrder`]s, once the fee tokens the full usomm amount buys are worth at least the minimum
+/// USD value, for [`LimitPriceOrder`]s, once the auction's unit price is at or below the limit, and
+/// for [`OracleDiscountOrder`]s, once the unit price is at least the minimum discount below the
+/// auction module's fresh prices. Each order is bid on once.
+pub struct ThresholdStrategy {
+    book: Arc<Mutex<OrderBook>>,
+    registry: DenomRegistry,
+    // usomm paid in fees for each bid, counted against an order's profit
+    bid_tx_fee_usomm: u128,
+}
+
+impl ThresholdStrategy {
+    pub fn new(
+        book: Arc<Mutex<OrderBook>>,
+        registry: DenomRegistry,
+        bid_tx_fee_usomm: u128,
+    ) -> Self {
+        Self {
+            book,
+            registry,
+            bid_tx_fee_usomm,
+        }
+    }
+
+    // Orders past their expiry are never bid on
+    fn expire_orders(&self, book: &mut OrderBook) {
+        let now = SystemTime::now();
+        let expired: Vec<String> = book
+            .pending()
+            .filter(|record| record.is_expired_at(now))
+            .map(|record| record.id.clone())
+            .collect();
+
+        for id in expired {
+            info!("order {id} expired");
+            if let Err(err) = book.expire(&id) {
+                error!("failed to record order {id} as expired: {err:?}");
+            }
+        }
+    }
+
+    // Orders that can't be covered by what's left of the budget would only be cancelled by the
+    // engine after being bid, so they're dropped up front.
+    fn cancel_orders_over_budget(&self, book: &mut OrderBook, snapshot: &MarketSnapshot) {
+        let over_budget: Vec<(String, String)> = book
+            .pending()
+            .filter_map(|record| {
+                let denom = record.order.fee_token();
+                let available = snapshot.budget.available(denom)?;
+                let maximum = self
+                    .registry
+                    .decimals(denom)
+                    .and_then(|d| record.order.maximum_usomm_in(d));
+                if maximum.map_or(true, |m| m.raw <= available.raw) {
+                    return None;
+                }
+
+                Some((
+                    record.id.clone(),
+                    format!(
+                        "maximum usomm in exceeds the remaining budget of {available} for {denom}"
+                    ),
+                ))
+            })
+            .collect();
+
+        for (id, reason) in over_budget {
+            warn!("cancelling order {id}: {reason}");
+            if let Err(err) = book.cancel(&id, Cancellation::Funds, reason) {
+                error!("failed to record order {id} as cancelled: {err:?}");
+            }
+        }
+    }
+
+    // Checks how much USD value we can get out with the max possible USOMM offer. Orders with a
+    // profit or discount requirement also price the USOMM paid in USD so only favourable trades
+    // are bid.
+    fn evaluate_usd_value_bid(
+        &self,
+        order: &UsdValueOrder,
+        usd_unit_value: f64,
+        somm_usd_price: Option<f64>,
+        auction: &AuctionView,
+    ) -> Option<Bid> {
+        debug!("evaluating bid for order: {:?}", order);
+        let decimals = match self.registry.decimals(&order.fee_token) {
+            Some(d) => d,
+            None => {
+                warn!("no decimals registered for {}", order.fee_token);
+
+                return None;
+            }
+        };
+        let auction_unit_price_in_usomm = auction.current_unit_price_in_usomm;
+        let remaining_tokens_for_sale = &auction.remaining_tokens_for_sale;
+
+        // the auction will give us the best possible price which makes this simpler
+        let max_allowed_usomm_offer = order.maximum_usomm_in.clone();
+        let max_possible_token_out =
+            auction_unit_price_in_usomm.div_int_floor(max_allowed_usomm_offer.raw)?;
+        let min_possible_token_out =
+            match TokenAmount::new(order.fee_token.clone(), max_possible_token_out)
+                .min(remaining_tokens_for_sale)
+            {
+                Ok(amount) => amount,
+                Err(err) => {
+                    warn!(
+                        "auction {} is not selling {}: {err:?}",
+                        auction.id, order.fee_token
+                    );
+
+                    return None;
+                }
+            };
+        let usd_value_out = min_possible_token_out.to_f64(decimals) * usd_unit_value;
+
+        debug!(
+            "usd_unit_value = {}, auction_unit_price_in_usomm = {}, remaining_tokens_for_sale = {}, max_allowed_usomm_offer = {}, min_possible_token_out = {}, usd_value_out = {}",
+            usd_unit_value, auction_unit_price_in_usomm, remaining_tokens_for_sale, max_allowed_usomm_offer, min_possible_token_out, usd_value_out
+        );
+
+        if order.minimum_usd_value_out <= usd_value_out {
+            info!(
+                "order qualifies for bid. usomm offer = {}, minimum token out = {}, usd value out = {}",
+                max_allowed_usomm_offer.display_with(&self.registry),
+                min_possible_token_out.display_with(&self.registry),
+                usd_value_out,
+            );
+
+            let bid = Bid {
+                auction_id: auction.id,
+                fee_token: order.fee_token.clone(),
+                maximum_usomm_in: max_allowed_usomm_offer,
+                minimum_tokens_out: min_possible_token_out,
+            };
+
+            let fill = self.simulate(auction, &bid)?;
+
+            if order.requires_profit() {
+                let Some(somm_usd_price) = somm_usd_price else {
+                    warn!("no SOMM price, can't check order {order:?} is profitable");
+
+                    return None;
+                };
+                let usd_value_in = usd_value_paid(
+                    &fill.total_usomm_paid,
+                    self.bid_tx_fee_usomm,
+                    somm_usd_price,
+                );
+                let usd_value_out =
+                    fill.total_fulfilled_sale_tokens.to_f64(decimals) * usd_unit_value;
+
+                if let Err(reason) = check_profit(order, usd_value_in, usd_value_out) {
+                    info!("order is not profitable enough: {reason}");
+
+                    return None;
+                }
+            }
+
+            return Some(bid);
+        } else {
+            info!(
+                " usd value out = {} does not meet minimum usd value out = {} for {}",
+                usd_value_out, order.minimum_usd_value_out, order.fee_token
+            );
+        }
+
+        None
+    }
+
+    // Bids for the order's quantity, or what's left of it in the auction, at the current unit
+    // price once it's at or below the limit
+    fn evaluate_limit_price_bid(
+        &self,
+        order: &LimitPriceOrder,
+        auction: &AuctionView,
+    ) -> Option<Bid> {
+        debug!("evaluating bid for order: {:?}", order);
+        let Some(decimals) = self.registry.decimals(&order.fee_token) else {
+            warn!("no decimals registered for {}", order.fee_token);
+
+            return None;
+        };
+        let Some(limit) = order.max_unit_price_in_usomm(decimals) else {
+            warn!("limit price of order {order:?} is out of range");
+
+            return None;
+        };
+        let unit_price = auction.current_unit_price_in_usomm;
+
+        if unit_price > limit {
+            info!(
+                "unit price = {} usomm does not meet limit = {} usomm for {}",
+                unit_price, limit, order.fee_token
+            );
+
+            return None;
+        }
+
+        let remaining_tokens_for_sale = &auction.remaining_tokens_for_sale;
+        let quantity = order.quantity.min(remaining_tokens_for_sale.raw);
+        if quantity < order.quantity {
+            info!(
+                "auction {} only has {} left, bidding for all of it",
+                auction.id,
+                remaining_tokens_for_sale.display_with(&self.registry)
+            );
+        }
+
+        let bid = Bid {
+            auction_id: auction.id,
+            fee_token: order.fee_token.clone(),
+            maximum_usomm_in: TokenAmount::usomm(unit_price.mul_int_ceil(quantity)?),
+            minimum_tokens_out: TokenAmount::new(order.fee_token.clone(), quantity),
+        };
+        info!(
+            "order qualifies for bid. unit price = {} usomm, limit = {} usomm, usomm offer = {}, minimum token out = {}",
+            unit_price,
+            limit,
+            bid.maximum_usomm_in.display_with(&self.registry),
+            bid.minimum_tokens_out.display_with(&self.registry),
+        );
+        self.simulate(auction, &bid)?;
+
+        Some(bid)
+    }
+
+    // Bids the order's full usomm amount once the auction's unit price is far enough below the
+    // fair price implied by the auction module's token prices. Stale prices are never used, the
+    // chain wouldn't use them either.
+    fn evaluate_oracle_discount_bid(
+        &self,
+        order: &OracleDiscountOrder,
+        snapshot: &MarketSnapshot,
+        auction: &AuctionView,
+    ) -> Option<Bid> {
+        debug!("evaluating bid for order: {:?}", order);
+        let mut prices = Vec::new();
+        for denom in [&order.fee_token, &Denom::USOMM] {
+            let Some(quote) = snapshot.token_price(denom) else {
+                warn!("no on-chain token price for {denom}, skipping bid evaluation");
+
+                return None;
+            };
+            if quote.is_stale {
+                warn!(
+                    "on-chain token price for {denom} is stale (last updated {} blocks ago), skipping bid evaluation",
+                    quote.age_in_blocks
+                );
+
+                return None;
+            }
+
+            prices.push(&quote.price);
+        }
+
+        let fair_unit_price = prices[0].unit_price_in(prices[1])?;
+        let discount = Dec::from_int(10_000 - order.min_discount_bps.min(10_000) as i128)?
+            .checked_div(Dec::from_int(10_000)?)?;
+        let limit = fair_unit_price.checked_mul(discount)?;
+        let unit_price = auction.current_unit_price_in_usomm;
+
+        if unit_price > limit {
+            info!(
+                "unit price = {} usomm is not {} bps below on-chain fair price = {} usomm for {}",
+                unit_price, order.min_discount_bps, fair_unit_price, order.fee_token
+            );
+
+            return None;
+        }
+
+        let max_possible_token_out = unit_price.div_int_floor(order.maximum_usomm_in.raw)?;
+        let minimum_tokens_out = TokenAmount::new(order.fee_token.clone(), max_possible_token_out)
+            .min(&auction.remaining_tokens_for_sale)
+            .ok()?;
+        let bid = Bid {
+            auction_id: auction.id,
+            fee_token: order.fee_token.clone(),
+            maximum_usomm_in: order.maximum_usomm_in.clone(),
+            minimum_tokens_out,
+        };
+        info!(
+            "order qualifies for bid. unit price = {} usomm, on-chain fair price = {} usomm, usomm offer = {}, minimum token out = {}",
+            unit_price,
+            fair_unit_price,
+            bid.maximum_usomm_in.display_with(&self.registry),
+            bid.minimum_tokens_out.display_with(&self.registry),
+        );
+        self.simulate(auction, &bid)?;
+
+        Some(bid)
+    }
+
+    // Logs the fill a bid is expected to get, or why the auction would reject it
+    fn simulate(&self, auction: &AuctionView, bid: &Bid) -> Option<Fill> {
+        match simulate_bid_at_price(auction, bid, auction.current_unit_price_in_usomm) {
+            SimulatedFill::Filled(fill) => {
+                info!(
+                    "expected fill: {} for {}",
+                    fill.total_fulfilled_sale_tokens
+                        .display_with(&self.registry),
+                    fill.total_usomm_paid.display_with(&self.registry),
+                );
+
+                Some(fill)
+            }
+            SimulatedFill::Rejected(rejection) => {
+                warn!("not submitting bid, the auction would reject it: {rejection}");
+
+                None
+            }
+        }
+    }
+}
+
+impl Strategy for ThresholdStrategy {
+    fn name(&self) -> &str {
+        "threshold"
+    }
+
+    fn price_denoms(&self) -> Vec<Denom> {
+        let book = self.book.lock().unwrap();
+        // submitted orders go back to pending if their bid never lands, and need pricing again
+        let mut denoms: Vec<Denom> = book
+            .orders()
+            .iter()
+            .filter(|record| record.status.is_open() && record.order.needs_usd_price())
+            .map(|record| record.order.fee_token().clone())
+            .collect();
+        denoms.sort();
+        denoms.dedup();
+
+        denoms
+    }
+
+    fn evaluate(&mut self, snapshot: &MarketSnapshot) -> Vec<BidIntent> {
+        let book = self.book.clone();
+        let mut book = book.lock().unwrap();
+        self.expire_orders(&mut book);
+        self.cancel_orders_over_budget(&mut book, snapshot);
+
+        let pending: Vec<OrderRecord> = book.pending().cloned().collect();
+        debug!("pending orders: {:?}", pending);
+
+        let mut intents = Vec::new();
+
+        // for each active auction, check if any orders qualify for a bid
+        for auction in &snapshot.active_auctions {
+            let auction_denom = auction.fee_token();
+            for record in pending
+                .iter()
+                .filter(|record| record.order.fee_token() == auction_denom)
+            {
+                // an order is only bid on once, even if several auctions could fill it
+                if intents
+                    .iter()
+                    .any(|i: &BidIntent| i.order_id.as_ref() == Some(&record.id))
+                {
+                    continue;
+                }
+
+                let bid = match &record.order {
+                    // if we don't have a usd price for the token, move on
+                    Order::UsdValue(order) => match snapshot.usd_price(auction_denom) {
+                        Some(usd_unit_value) => self.evaluate_usd_value_bid(
+                            order,
+                            usd_unit_value,
+                            snapshot.somm_usd_price,
+                            auction,
+                        ),
+                        None => {
+                            warn!("no USD price for {auction_denom}, skipping bid evaluation");
+
+                            None
+                        }
+                    },
+                    Order::LimitPrice(order) => self.evaluate_limit_price_bid(order, auction),
+                    Order::OracleDiscount(order) => {
+                        self.evaluate_oracle_discount_bid(order, snapshot, auction)
+                    }
+                };
+
+                let Some(bid) = bid else {
+                    continue;
+                };
+                if let Err(err) = book.trigger(&record.id, &bid) {
+                    error!(
+                        "failed to record bid for order {}, not bidding: {err:?}",
+                        record.id
+                    );
+
+                    continue;
+                }
+
+                info!("order {} triggered: {:?}", record.id, record.order);
+                intents.push(BidIntent {
+                    strategy: self.name().to_string(),
+                    order_id: Some(record.id.clone()),
+                    bid,
+                });
+            }
+        }
+
+        intents
+    }
+
+    // orders with uncertain bids may go back to pending, so they keep the strategy running
+    fn is_finished(&self) -> bool {
+        !self
+            .book
+            .lock()
+            .unwrap()
+            .orders()
+            .iter()
+            .any(|record| record.status.is_open())
+    }
+}
+
+// USD value of the usomm a bid pays, including the transaction fee
+fn usd_value_paid(total_usomm_paid: &TokenAmount, fee_usomm: u128, somm_usd_price: f64) -> f64 {
+    let usomm_in = total_usomm_paid.raw.saturating_add(fee_usomm);
+
+    TokenAmount::usomm(usomm_in).to_f64(USOMM_DECIMALS) * somm_usd_price
+}
+
+// Compares the USD value paid, including fees, with the USD value received against the order's
+// profit and discount requirements
+fn check_profit(
+    order: &UsdValueOrder,
+    usd_value_in: f64,
+    usd_value_out: f64,
+) -> Result<(), String> {
+    let profit_usd = usd_value_out - usd_value_in;
+    debug!("usd value in = {usd_value_in}, usd value out = {usd_value_out}, profit = {profit_usd}");
+
+    if let Some(min_profit_usd) = order.min_profit_usd {
+        if profit_usd < min_profit_usd {
+            return Err(format!(
+                "profit of ${profit_usd:.2} is below the minimum of ${min_profit_usd:.2}"
+            ));
+        }
+    }
+
+    if let Some(min_discount_bps) = order.min_discount_bps {
+        let discount_bps = if usd_value_out > 0.0 {
+            profit_usd / usd_value_out * 10_000.0
+        } else {
+            f64::NEG_INFINITY
+        };
+        if discount_bps < min_discount_bps as f64 {
+            return Err(format!(
+                "discount of {discount_bps:.0} bps is below the minimum of {min_discount_bps} bps"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sommelier_auction::client::{bid_tx_fee_usomm, DEFAULT_GAS_PRICE_USOMM};
+
+    use super::*;
+    use crate::{
+        book::OrderStatus,
+        fixtures::{auction, bid, entry, quote, snapshot, tx},
+        order::OrderEntry,
+    };
+
+    fn order(min_profit_usd: Option<f64>, min_discount_bps: Option<u32>) -> UsdValueOrder {
+        UsdValueOrder {
+            fee_token: Denom::USDC,
+            maximum_usomm_in: TokenAmount::usomm(1_000_000),
+            minimum_usd_value_out: 0.0,
+            min_profit_usd,
+            min_discount_bps,
+        }
+    }
+
+    #[test]
+    fn test_min_profit_boundary() {
+        let order = order(Some(32.0), None);
+
+        assert!(check_profit(&order, 96.0, 128.0).is_ok());
+        assert!(check_profit(&order, 96.0, 127.99).is_err());
+        assert!(check_profit(&order, 96.01, 128.0).is_err());
+    }
+
+    #[test]
+    fn test_min_discount_boundary() {
+        // 32 of 128 is 2500 bps
+        assert!(check_profit(&order(None, Some(2500)), 96.0, 128.0).is_ok());
+        assert!(check_profit(&order(None, Some(2501)), 96.0, 128.0).is_err());
+        assert!(check_profit(&order(None, Some(1)), 128.0, 128.0).is_err());
+        assert!(check_profit(&order(None, Some(0)), 128.0, 128.0).is_ok());
+        assert!(check_profit(&order(None, Some(0)), 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_both_requirements() {
+        let order = order(Some(32.0), Some(2501));
+
+        assert!(check_profit(&order, 96.0, 128.0).is_err());
+        assert!(check_profit(&order, 95.0, 128.0).is_ok());
+    }
+
+    #[test]
+    fn test_fee_counts_against_profit() {
+        // 1 SOMM = $2, so the default 5000 usomm fee costs a cent
+        let fee = bid_tx_fee_usomm(DEFAULT_GAS_PRICE_USOMM).unwrap();
+        let paid = TokenAmount::usomm(48_000_000);
+        assert_eq!(usd_value_paid(&paid, 0, 2.0), 96.0);
+
+        let usd_value_in = usd_value_paid(&paid, fee, 2.0);
+        assert!((usd_value_in - 96.01).abs() < 1e-9);
+
+        let order = order(Some(32.0), None);
+        assert!(check_profit(&order, usd_value_paid(&paid, 0, 2.0), 128.0).is_ok());
+        assert!(check_profit(&order, usd_value_in, 128.0).is_err());
+    }
+
+    #[test]
+    fn test_price_denoms_cover_open_orders() {
+        let weth = OrderEntry {
+            id: Some("weth".to_string()),
+            ..Order::from(UsdValueOrder {
+                fee_token: Denom::WETH,
+                ..order(None, None)
+            })
+            .into()
+        };
+        let mut book = OrderBook::open(None, &[entry(Some("usdc"), 1000), weth]).unwrap();
+        // a submitted bid may go back to pending, so its denom is still priced
+        book.trigger("usdc", &bid(1000)).unwrap();
+        book.submit("usdc", &bid(1000), &tx("usdc")).unwrap();
+        let book = Arc::new(Mutex::new(book));
+        let strategy = ThresholdStrategy::new(book.clone(), DenomRegistry::builtin(), 0);
+
+        assert_eq!(strategy.price_denoms(), vec![Denom::USDC, Denom::WETH]);
+
+        book.lock()
+            .unwrap()
+            .cancel("weth", Cancellation::Funds, "over budget")
+            .unwrap();
+        assert_eq!(strategy.price_denoms(), vec![Denom::USDC]);
+    }
+
+    fn strategy(entries: &[OrderEntry]) -> ThresholdStrategy {
+        let book = OrderBook::open(None, entries).unwrap();
+
+        ThresholdStrategy::new(Arc::new(Mutex::new(book)), DenomRegistry::builtin(), 0)
+    }
+
+    // 2 SOMM per whole USDC is 2 usomm per base unit
+    fn limit_order(quantity: u128) -> LimitPriceOrder {
+        LimitPriceOrder {
+            fee_token: Denom::USDC,
+            max_usomm_per_token: 2_000_000,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_limit_price_boundary() {
+        let strategy = strategy(&[]);
+        let order = limit_order(100);
+
+        let bid = strategy
+            .evaluate_limit_price_bid(&order, &auction(1, &Denom::USDC, 1000, "2"))
+            .unwrap();
+        assert_eq!(bid.auction_id, 1);
+        assert_eq!(bid.maximum_usomm_in, TokenAmount::usomm(200));
+        assert_eq!(bid.minimum_tokens_out, TokenAmount::new(Denom::USDC, 100));
+
+        let above = auction(1, &Denom::USDC, 1000, "2.000000000000000001");
+        assert_eq!(strategy.evaluate_limit_price_bid(&order, &above), None);
+    }
+
+    #[test]
+    fn test_limit_price_quantity_capped_by_supply() {
+        let strategy = strategy(&[]);
+
+        let bid = strategy
+            .evaluate_limit_price_bid(&limit_order(100), &auction(1, &Denom::USDC, 60, "1.5"))
+            .unwrap();
+        assert_eq!(bid.minimum_tokens_out, TokenAmount::new(Denom::USDC, 60));
+        assert_eq!(bid.maximum_usomm_in, TokenAmount::usomm(90));
+    }
+
+    #[test]
+    fn test_limit_price_offer_rounds_up() {
+        let strategy = strategy(&[]);
+        let unit_price = "1.333333333333333333";
+
+        // 133.33 usomm for 100 units
+        let bid = strategy
+            .evaluate_limit_price_bid(
+                &limit_order(100),
+                &auction(1, &Denom::USDC, 1000, unit_price),
+            )
+            .unwrap();
+        assert_eq!(bid.maximum_usomm_in, TokenAmount::usomm(134));
+        let expected = unit_price
+            .parse::<Dec>()
+            .unwrap()
+            .mul_int_ceil(100)
+            .unwrap();
+        assert_eq!(bid.maximum_usomm_in.raw, expected);
+
+        // 4.5 usomm for 3 units
+        let bid = strategy
+            .evaluate_limit_price_bid(&limit_order(3), &auction(1, &Denom::USDC, 1000, "1.5"))
+            .unwrap();
+        assert_eq!(bid.maximum_usomm_in, TokenAmount::usomm(5));
+    }
+
+    #[test]
+    fn test_order_is_bid_on_once() {
+        let entries = [OrderEntry {
+            id: Some("limit".to_string()),
+            ..Order::from(limit_order(100)).into()
+        }];
+        let mut strategy = strategy(&entries);
+        let snapshot = snapshot(vec![
+            auction(1, &Denom::USDC, 1000, "1.5"),
+            auction(2, &Denom::USDC, 1000, "1"),
+            auction(3, &Denom::WETH, 1000, "1"),
+        ]);
+
+        let intents = strategy.evaluate(&snapshot);
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].order_id.as_deref(), Some("limit"));
+        assert_eq!(intents[0].bid.auction_id, 1);
+        assert_eq!(
+            strategy.book.lock().unwrap().get("limit").unwrap().status,
+            OrderStatus::Triggered
+        );
+
+        // triggered orders aren't bid on again
+        assert!(strategy.evaluate(&snapshot).is_empty());
+    }
+
+    fn discount_order(fee_token: &Denom, min_discount_bps: u32) -> OracleDiscountOrder {
+        OracleDiscountOrder {
+            fee_token: fee_token.clone(),
+            maximum_usomm_in: TokenAmount::usomm(1_000_000),
+            min_discount_bps,
+        }
+    }
+
+    // USDC at $1 and SOMM at $0.10 is a fair price of 10 usomm per uusdc
+    fn usdc_snapshot(unit_price: &str, usdc_stale: bool, usomm_stale: bool) -> MarketSnapshot {
+        MarketSnapshot {
+            token_prices: vec![
+                quote(&Denom::USDC, 6, "1", usdc_stale),
+                quote(&Denom::USOMM, 6, "0.1", usomm_stale),
+            ],
+            ..snapshot(vec![auction(1, &Denom::USDC, 1_000_000, unit_price)])
+        }
+    }
+
+    fn evaluate_discount(
+        strategy: &ThresholdStrategy,
+        order: &OracleDiscountOrder,
+        snapshot: &MarketSnapshot,
+    ) -> Option<Bid> {
+        strategy.evaluate_oracle_discount_bid(order, snapshot, &snapshot.active_auctions[0])
+    }
+
+    #[test]
+    fn test_oracle_discount_boundary() {
+        let strategy = strategy(&[]);
+        // 1000 bps below 10 usomm is 9
+        let order = discount_order(&Denom::USDC, 1000);
+
+        let bid = evaluate_discount(&strategy, &order, &usdc_snapshot("9", false, false)).unwrap();
+        assert_eq!(bid.maximum_usomm_in, TokenAmount::usomm(1_000_000));
+        assert_eq!(
+            bid.minimum_tokens_out,
+            TokenAmount::new(Denom::USDC, 111_111)
+        );
+
+        let above = usdc_snapshot("9.000000000000000001", false, false);
+        assert_eq!(evaluate_discount(&strategy, &order, &above), None);
+
+        // the tokens out are capped by the supply
+        let snapshot = MarketSnapshot {
+            active_auctions: vec![auction(1, &Denom::USDC, 1000, "9")],
+            ..usdc_snapshot("9", false, false)
+        };
+        let bid = evaluate_discount(&strategy, &order, &snapshot).unwrap();
+        assert_eq!(bid.minimum_tokens_out, TokenAmount::new(Denom::USDC, 1000));
+    }
+
+    #[test]
+    fn test_oracle_discount_needs_fresh_prices() {
+        let strategy = strategy(&[]);
+        let order = discount_order(&Denom::USDC, 1000);
+
+        assert_eq!(
+            evaluate_discount(&strategy, &order, &usdc_snapshot("5", true, false)),
+            None
+        );
+        assert_eq!(
+            evaluate_discount(&strategy, &order, &usdc_snapshot("5", false, true)),
+            None
+        );
+
+        for denom in [Denom::USDC, Denom::USOMM] {
+            let mut snapshot = usdc_snapshot("5", false, false);
+            snapshot.token_prices.retain(|p| p.price.denom != denom);
+
+            assert_eq!(evaluate_discount(&strategy, &order, &snapshot), None);
+        }
+        assert!(evaluate_discount(&strategy, &order, &usdc_snapshot("5", false, false)).is_some());
+    }
+
+    #[test]
+    fn test_oracle_discount_18_decimals() {
+        let strategy = strategy(&[]);
+        // 1 wei of WETH at $2000 is worth 0.00000004 usomm with SOMM at $0.05, 100 bps below that
+        // is 0.0000000396
+        let order = discount_order(&Denom::WETH, 100);
+        let snapshot = |unit_price| MarketSnapshot {
+            token_prices: vec![
+                quote(&Denom::WETH, 18, "2000", false),
+                quote(&Denom::USOMM, 6, "0.05", false),
+            ],
+            ..snapshot(vec![auction(1, &Denom::WETH, 10u128.pow(18), unit_price)])
+        };
+
+        let bid = evaluate_discount(&strategy, &order, &snapshot("0.0000000396")).unwrap();
+        assert_eq!(
+            bid.minimum_tokens_out,
+            TokenAmount::new(Denom::WETH, 25_252_525_252_525)
+        );
+
+        let above = snapshot("0.000000039600000001");
+        assert_eq!(evaluate_discount(&strategy, &order, &above), None);
+    }
+}
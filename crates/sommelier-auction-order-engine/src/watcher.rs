@@ -1,34 +1,61 @@
-use std::collections::HashMap;
-use std::str::FromStr;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use eyre::{eyre, Result};
-use sommelier_auction::{auction::Auction, bid::Bid, client::Client, denom::Denom};
+use sommelier_auction::{
+    auction::TokenPrice,
+    client::Client,
+    denom::Denom,
+    parameters::AuctionParameters,
+    registry::DenomRegistry,
+    version::VersionCheck,
+    view::{AuctionView, TokenPriceQuote, TokenPriceView},
+};
 use tokio::sync::mpsc::Sender;
 use tracing::{debug, error, info, warn};
 
-use crate::{order::Order, util};
+use crate::{
+    budget::UsommBudget,
+    strategy::{BidIntent, MarketSnapshot, Strategy},
+};
 
-// This is a temporary type to house the auction monitoring function so we can
-// spawn a thread to run it. In the future we should think about a generalized
-// "Strategy" trait that has a Sender<Bid> and decides when to send a bid over
-// the channel. The OrderEngine could then take in an arbitrary strategy, run it,
-// and relay bids sent over the channel to a bidder service.
+/// Gathers market data, shows it to each [`Strategy`] and relays the bids they want placed over a
+/// channel to the engine's bid submission service.
 pub struct Watcher {
-    active_auctions: Vec<Auction>,
+    active_auctions: Vec<AuctionView>,
+    auction_parameters: Option<AuctionParameters>,
+    bidder_address: Option<String>,
+    budget: Arc<Mutex<UsommBudget>>,
     client: Option<Client>,
     grpc_endpoint: String,
-    orders: HashMap<Denom, Vec<Order>>,
     prices: HashMap<Denom, f64>,
+    registry: DenomRegistry,
+    somm_usd_price: Option<f64>,
+    strategies: Vec<Box<dyn Strategy>>,
 }
 
 impl Watcher {
-    pub fn new(orders: HashMap<Denom, Vec<Order>>, grpc_endpoint: String) -> Self {
+    pub fn new(
+        strategies: Vec<Box<dyn Strategy>>,
+        registry: DenomRegistry,
+        auction_parameters: Option<AuctionParameters>,
+        grpc_endpoint: String,
+        bidder_address: Option<String>,
+        budget: Arc<Mutex<UsommBudget>>,
+    ) -> Self {
         Self {
             active_auctions: Vec::new(),
+            auction_parameters,
+            bidder_address,
+            budget,
             client: None,
             grpc_endpoint,
-            orders,
             prices: HashMap::new(),
+            registry,
+            somm_usd_price: None,
+            strategies,
         }
     }
 
@@ -58,28 +85,135 @@ impl Watcher {
         Ok(())
     }
 
+    // A failed refresh keeps the last known price, it's only used to check orders are profitable
+    async fn refresh_somm_price(&mut self) {
+        debug!("refreshing SOMM price");
+        match price_feed::get_somm_price(None).await {
+            Ok(price) => self.somm_usd_price = Some(price),
+            Err(err) => warn!("failed to get SOMM price: {err:?}"),
+        }
+    }
+
+    // Governance can change how auctions behave at any time, so changes are logged as soon as
+    // they're seen.
+    async fn refresh_auction_parameters(&mut self) -> Result<()> {
+        debug!("refreshing auction parameters");
+        let parameters = self.client.as_mut().unwrap().auction_parameters().await?;
+
+        if let Some(previous) = &self.auction_parameters {
+            for change in previous.diff(&parameters) {
+                warn!("auction parameter changed: {change}");
+            }
+        }
+        if let Err(err) = parameters.validate() {
+            warn!("auction parameters are out of bounds: {err:?}");
+        }
+
+        self.auction_parameters = Some(parameters);
+
+        Ok(())
+    }
+
     async fn refresh_active_auctions(&mut self) -> Result<()> {
         debug!("refreshing active auctions");
         let active_auctions = self.client.as_mut().unwrap().active_auctions().await?;
-        self.active_auctions = active_auctions;
+        self.active_auctions = active_auctions
+            .iter()
+            .filter_map(|auction| match AuctionView::try_from(auction) {
+                Ok(view) => Some(view),
+                Err(err) => {
+                    error!("skipping auction: {err:?}");
+
+                    None
+                }
+            })
+            .collect();
 
         Ok(())
     }
 
-    pub async fn monitor_auctions(&mut self, tx: Sender<Bid>) -> Result<()> {
-        self.client =
-            Some(Client::with_endpoints("".to_string(), self.grpc_endpoint.clone()).await?);
+    // Maps the coingecko id of every denom a strategy needs a price for back to the denom
+    fn coingecko_ids(&self) -> HashMap<String, Denom> {
+        self.strategies
+            .iter()
+            .flat_map(|s| s.price_denoms())
+            .filter_map(|d| {
+                let id = self.registry.price_source_id(&d);
+                if id.is_none() {
+                    warn!("no price source id registered for {d}, it will not be priced");
+                }
+
+                id.map(|id| (id.to_string(), d))
+            })
+            .collect()
+    }
+
+    // Everything besides the active auctions is best effort. Strategies decide what to do when
+    // data they need is missing.
+    async fn snapshot(&mut self) -> Result<MarketSnapshot> {
+        let client = self.client.as_mut().unwrap();
+        let height = client.latest_block_height().await?;
+
+        let token_prices = match client.token_prices().await {
+            Ok(prices) => {
+                let price_max_block_age = self
+                    .auction_parameters
+                    .as_ref()
+                    .map(|p| p.price_max_block_age);
+                if price_max_block_age.is_none() {
+                    warn!(
+                        "auction parameters are unknown, treating on-chain token prices as stale"
+                    );
+                }
+
+                quote_token_prices(&prices, height, price_max_block_age)
+            }
+            Err(err) => {
+                error!("failed to refresh token prices: {err:?}");
+
+                Vec::new()
+            }
+        };
+
+        let balances = match &self.bidder_address {
+            Some(address) => match client.balances(address).await {
+                Ok(balances) => balances,
+                Err(err) => {
+                    error!("failed to refresh balances of {address}: {err:?}");
+
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+
+        Ok(MarketSnapshot {
+            height,
+            active_auctions: self.active_auctions.clone(),
+            usd_prices: self.prices.clone(),
+            somm_usd_price: self.somm_usd_price,
+            token_prices,
+            auction_parameters: self.auction_parameters.clone(),
+            balances,
+            budget: self.budget.lock().unwrap().clone(),
+        })
+    }
+
+    pub async fn monitor_auctions(&mut self, tx: Sender<BidIntent>) -> Result<()> {
+        // the engine has already checked the node version
+        self.client = Some(
+            Client::with_endpoints_and_version_check(
+                "".to_string(),
+                self.grpc_endpoint.clone(),
+                VersionCheck::Skip,
+            )
+            .await?,
+        );
         let mut count = 0;
-        let coingecko_ids = self
-            .orders
-            .keys()
-            .map(|d| (util::denom_to_coingecko_id(*d), *d))
-            .collect::<HashMap<String, Denom>>();
         loop {
-            debug!("orders in state: {:?}", self.orders);
             info!("monitoring auctions");
-            if self.orders.iter().all(|(_, v)| v.is_empty()) {
-                info!("no more orders! shutting down");
+            if self.strategies.iter().all(|s| s.is_finished()) {
+                info!("all strategies are finished! shutting down");
 
                 return Ok(());
             }
@@ -101,117 +235,100 @@ impl Watcher {
 
             // everything few loops so we don't hit the rate limit
             if count % 4 == 0 {
-                self.refresh_prices(coingecko_ids.clone()).await?;
+                // the denoms strategies need priced change as their orders do. orders priced only
+                // on chain don't need coingecko at all.
+                let coingecko_ids = self.coingecko_ids();
+                if !coingecko_ids.is_empty() {
+                    self.refresh_prices(coingecko_ids).await?;
+                    self.refresh_somm_price().await;
+                }
+
+                if let Err(err) = self.refresh_auction_parameters().await {
+                    error!("failed to refresh auction parameters: {err:?}");
+                }
             }
 
             count += 1;
 
-            let mut sent_orders = Vec::new();
-
-            // for each active auction, check if any orders qualify for a bid
-            info!("evaluating orders for auctions");
-            for auction in &self.active_auctions {
-                let auction_denom = match Denom::try_from(
-                    auction
-                        .starting_tokens_for_sale
-                        .clone()
-                        .unwrap()
-                        .denom
-                        .clone(),
-                ) {
-                    Ok(d) => d,
-                    Err(err) => {
-                        error!("failed to parse auction denom from auction object: {err:?}");
-
-                        continue;
-                    }
-                };
-                if let Some(orders) = self.orders.get(&auction_denom) {
-                    for order in orders {
-                        // if we don't have a usd price for the token, move on
-                        if let Some(usd_unit_value) = self.prices.get(&auction_denom) {
-                            if let Some(bid) = self.evaluate_bid(order, *usd_unit_value, auction) {
-                                // submit bid
-                                if let Err(err) = tx.send(bid).await {
-                                    panic!("bid sender errored unexpectedly: {err:?}");
-                                }
-
-                                sent_orders.push(order.clone());
-                            }
-                        } else {
-                            warn!("no USD price for {auction_denom}, skipping bid evaluation");
-                        }
-                    }
+            let snapshot = match self.snapshot().await {
+                Ok(snapshot) => snapshot,
+                Err(err) => {
+                    error!("failed to build market snapshot: {err:?}");
+                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+
+                    continue;
                 }
-            }
+            };
 
-            // remove sent orders from the orders map
-            for order in sent_orders {
-                info!("removing sent order from state: {order:?}");
-                let orders = self.orders.get_mut(&order.fee_token).unwrap();
-                orders.retain(|o| o != &order);
+            info!("evaluating strategies at height {}", snapshot.height);
+            for strategy in self.strategies.iter_mut().filter(|s| !s.is_finished()) {
+                for intent in strategy.evaluate(&snapshot) {
+                    if let Err(err) = tx.send(intent).await {
+                        panic!("bid sender errored unexpectedly: {err:?}");
+                    }
+                }
             }
 
             // roughly every block
             tokio::time::sleep(std::time::Duration::from_secs(6)).await;
         }
     }
+}
 
-    // Collin: Currently not checking USOMM price in USD and thus not guaranteeing a profitable
-    // arbitrage. We're simply checking how much USD value we can get out with the max possible
-    // USOMM offer.
-    fn evaluate_bid(&self, order: &Order, usd_unit_value: f64, auction: &Auction) -> Option<Bid> {
-        debug!("evaluating bid for order: {:?}", order);
-        let denom = order.fee_token.clone();
-        let usd_unit_value = usd_unit_value / 10u128.pow(denom.decimals() as u32) as f64;
-        let auction_unit_price_in_usomm =
-            f64::from_str(&auction.current_unit_price_in_usomm).unwrap();
-        // divide by 1e18 because sdk.Dec is just a BigInt and the exponent info is lost when
-        // serialized into a proto.
-        let auction_unit_price_in_usomm = auction_unit_price_in_usomm / 1_000_000_000_000_000_000.0;
-        let remaining_tokens_for_sale = auction
-            .remaining_tokens_for_sale
-            .clone()
-            .unwrap()
-            .amount
-            .parse::<u128>()
-            .unwrap();
-
-        // the auction will give us the best possible price which makes this simpler
-        let max_allowed_usomm_offer = order.maximum_usomm_in;
-        let min_possible_token_out = std::cmp::min(
-            (max_allowed_usomm_offer as f64 / auction_unit_price_in_usomm) as u128,
-            remaining_tokens_for_sale,
-        );
-        let usd_value_out = min_possible_token_out as f64 * usd_unit_value;
+// Quotes each token price's freshness at `height`. Without the auction module's
+// `price_max_block_age` there's no telling whether a price is fresh, so every price is stale.
+fn quote_token_prices(
+    prices: &[TokenPrice],
+    height: u64,
+    price_max_block_age: Option<u64>,
+) -> Vec<TokenPriceQuote> {
+    prices
+        .iter()
+        .filter_map(|p| match TokenPriceView::try_from(p) {
+            Ok(price) => Some(match price_max_block_age {
+                Some(max_age) => price.quote_at(height, max_age),
+                None => TokenPriceQuote {
+                    is_stale: true,
+                    ..price.quote_at(height, 0)
+                },
+            }),
+            Err(err) => {
+                warn!("skipping token price: {err:?}");
 
-        debug!(
-            "usd_unit_value = {}, auction_unit_price_in_usomm = {}, remaining_tokens_for_sale = {}, max_allowed_usomm_offer = {}, min_possible_token_out = {}, usd_value_out = {}",
-            usd_unit_value, auction_unit_price_in_usomm, remaining_tokens_for_sale, max_allowed_usomm_offer, min_possible_token_out, usd_value_out
-        );
+                None
+            }
+        })
+        .collect()
+}
 
-        if order.minimum_usd_value_out <= usd_value_out {
-            info!(
-                "order qualifies for bid. usomm offer = {}, minimum token out = {}, usd value out = {} for {}",
-                max_allowed_usomm_offer,
-                min_possible_token_out,
-                usd_value_out,
-                order.fee_token
-            );
-
-            return Some(Bid {
-                auction_id: auction.id,
-                fee_token: order.fee_token,
-                maximum_usomm_in: max_allowed_usomm_offer as u128,
-                minimum_tokens_out: min_possible_token_out,
-            });
-        } else {
-            info!(
-                " usd value out = {} does not meet minimum usd value out = {} for {}",
-                usd_value_out, order.minimum_usd_value_out, order.fee_token
-            );
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_price(last_updated_block: u64) -> TokenPrice {
+        TokenPrice {
+            denom: Denom::WETH.to_string(),
+            exponent: 18,
+            usd_price: "2000.0".to_string(),
+            last_updated_block,
         }
+    }
+
+    #[test]
+    fn test_quote_token_prices() {
+        let quotes = quote_token_prices(&[token_price(90), token_price(80)], 100, Some(10));
+
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes[0].age_in_blocks, 10);
+        assert!(!quotes[0].is_stale);
+        assert!(quotes[1].is_stale);
+    }
+
+    #[test]
+    fn test_prices_are_stale_without_auction_parameters() {
+        let quotes = quote_token_prices(&[token_price(100)], 100, None);
 
-        None
+        assert_eq!(quotes[0].age_in_blocks, 0);
+        assert!(quotes[0].is_stale);
     }
 }
@@ -23,6 +23,8 @@ const COSMOS_SDK_PROTO_REGEX: &str = "(super::)+cosmos";
 /// the output directory
 const TMP_PATH: &str = "/tmp/sommelier-auction-proto-build/";
 const OUT_PATH: &str = "../sommelier-auction-proto/src/gen/";
+/// Keep the major version in sync with `SUPPORTED_SOMMELIER_MAJOR_VERSION` in the
+/// sommelier-auction crate, which clients check nodes against at runtime.
 const SOMMELIER_RELEASE_VERSION: &str = "v7.0.1";
 
 // All paths must end with a / and either be absolute or include a ./ to reference the current
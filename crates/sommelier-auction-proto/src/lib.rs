@@ -7,3 +7,6 @@ pub mod auction {
 pub mod cellarfees {
     include!("gen/cellarfees.v1.rs");
 }
+
+pub mod gravity;
+pub mod ibc_transfer;
@@ -0,0 +1,369 @@
+//! Fixed-point token amounts. Amounts are always held in base units (i.e. usomm or wei) and only
+//! converted to whole-token units for parsing and display, so no precision is lost on 18 decimal
+//! tokens.
+use std::{fmt::Display, str::FromStr};
+
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cosmos_sdk_proto::cosmos::base::v1beta1::Coin, dec::Dec, denom::Denom, registry::DenomRegistry,
+};
+
+/// An amount of a token in its base units
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TokenAmount {
+    pub denom: Denom,
+    /// The amount in base units
+    #[serde(with = "raw_string")]
+    pub raw: u128,
+}
+
+impl TokenAmount {
+    pub fn new(denom: Denom, raw: u128) -> Self {
+        Self { denom, raw }
+    }
+
+    /// An amount of usomm
+    pub fn usomm(raw: u128) -> Self {
+        Self::new(Denom::USOMM, raw)
+    }
+
+    /// A zero amount of `denom`
+    pub fn zero(denom: Denom) -> Self {
+        Self::new(denom, 0)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw == 0
+    }
+
+    /// Parses a whole-token amount like "1.5" into base units of a token with `decimals` decimals.
+    /// Errors if the amount has more fractional digits than the token supports.
+    pub fn from_decimal_str(denom: Denom, amount: &str, decimals: u8) -> Result<Self> {
+        let amount = amount.trim();
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(eyre!("invalid amount {amount:?}: no digits"));
+        }
+        if !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return Err(eyre!(
+                "invalid amount {amount:?}: expected a non-negative decimal number"
+            ));
+        }
+        if fraction.len() > decimals as usize {
+            return Err(eyre!(
+                "invalid amount {amount:?}: {denom} only has {decimals} decimals"
+            ));
+        }
+
+        let digits = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+        let raw = digits
+            .parse::<u128>()
+            .map_err(|_| eyre!("invalid amount {amount:?}: overflows u128"))?;
+
+        Ok(Self::new(denom, raw))
+    }
+
+    /// Parses a whole-token amount followed by a registered symbol or denom, i.e. "1.5 WETH" or
+    /// "2 usomm"
+    pub fn parse_with_registry(s: &str, registry: &DenomRegistry) -> Result<Self> {
+        let (amount, unit) = s
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| eyre!("invalid amount {s:?}: expected \"<amount> <symbol>\""))?;
+        let unit = unit.trim();
+        let info = match registry.get_by_symbol(unit) {
+            Some(info) => info,
+            None => registry.resolve(&Denom::new(unit)?)?,
+        };
+
+        Self::from_decimal_str(info.denom.clone(), amount, info.decimals)
+    }
+
+    /// Formats the amount in whole-token units with `decimals` decimals, trimming trailing zeros
+    pub fn to_decimal_string(&self, decimals: u8) -> String {
+        let decimals = decimals as usize;
+        let digits = format!("{:0>width$}", self.raw, width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let fraction = fraction.trim_end_matches('0');
+
+        if fraction.is_empty() {
+            whole.to_string()
+        } else {
+            format!("{whole}.{fraction}")
+        }
+    }
+
+    /// The amount in whole-token units as a float. Only for display and rough valuation, never
+    /// for computing on-chain amounts.
+    pub fn to_f64(&self, decimals: u8) -> f64 {
+        self.raw as f64 / 10f64.powi(decimals as i32)
+    }
+
+    /// The amount in whole-token units as an exact [`Dec`]. `None` for tokens with more than 18
+    /// decimals or amounts too large for a [`Dec`].
+    pub fn to_dec(&self, decimals: u8) -> Option<Dec> {
+        let scale = 10i128.checked_pow(Dec::PRECISION.checked_sub(decimals as u32)?)?;
+        let atomics = i128::try_from(self.raw).ok()?.checked_mul(scale)?;
+
+        Some(Dec::from_atomics(atomics))
+    }
+
+    /// Displays the amount in whole-token units with the registered symbol, i.e. "1.5 WETH". Falls
+    /// back to the base unit form "1500000000000000000gravity0x..." for unregistered denoms.
+    pub fn display_with(&self, registry: &DenomRegistry) -> String {
+        match registry.get(&self.denom) {
+            Some(info) => format!("{} {}", self.to_decimal_string(info.decimals), info.symbol),
+            None => self.to_string(),
+        }
+    }
+
+    pub fn checked_add(&self, other: &TokenAmount) -> Result<Self> {
+        self.ensure_same_denom(other)?;
+        let raw = self
+            .raw
+            .checked_add(other.raw)
+            .ok_or_else(|| eyre!("overflow adding {other} to {self}"))?;
+
+        Ok(Self::new(self.denom.clone(), raw))
+    }
+
+    pub fn checked_sub(&self, other: &TokenAmount) -> Result<Self> {
+        self.ensure_same_denom(other)?;
+        let raw = self
+            .raw
+            .checked_sub(other.raw)
+            .ok_or_else(|| eyre!("underflow subtracting {other} from {self}"))?;
+
+        Ok(Self::new(self.denom.clone(), raw))
+    }
+
+    /// Subtracts `other`, stopping at zero
+    pub fn saturating_sub(&self, other: &TokenAmount) -> Result<Self> {
+        self.ensure_same_denom(other)?;
+
+        Ok(Self::new(
+            self.denom.clone(),
+            self.raw.saturating_sub(other.raw),
+        ))
+    }
+
+    pub fn checked_mul(&self, factor: u128) -> Option<Self> {
+        Some(Self::new(self.denom.clone(), self.raw.checked_mul(factor)?))
+    }
+
+    pub fn checked_div(&self, divisor: u128) -> Option<Self> {
+        Some(Self::new(
+            self.denom.clone(),
+            self.raw.checked_div(divisor)?,
+        ))
+    }
+
+    /// The smaller of two amounts of the same denom
+    pub fn min(&self, other: &TokenAmount) -> Result<Self> {
+        self.ensure_same_denom(other)?;
+
+        Ok(Self::new(self.denom.clone(), self.raw.min(other.raw)))
+    }
+
+    fn ensure_same_denom(&self, other: &TokenAmount) -> Result<()> {
+        if self.denom != other.denom {
+            return Err(eyre!("denom mismatch: {} and {}", self.denom, other.denom));
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays in the Cosmos coin format, i.e. "1000000usomm"
+impl Display for TokenAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.raw, self.denom)
+    }
+}
+
+/// Parses the Cosmos coin format, i.e. "1000000usomm"
+impl FromStr for TokenAmount {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| eyre!("invalid coin {s:?}: missing denom"))?;
+        let (amount, denom) = s.split_at(split);
+        if amount.is_empty() {
+            return Err(eyre!("invalid coin {s:?}: missing amount"));
+        }
+
+        let raw = amount
+            .parse()
+            .map_err(|e| eyre!("invalid coin amount {amount:?}: {e}"))?;
+
+        Ok(Self::new(Denom::new(denom)?, raw))
+    }
+}
+
+impl TryFrom<Coin> for TokenAmount {
+    type Error = eyre::Report;
+
+    fn try_from(coin: Coin) -> Result<Self, Self::Error> {
+        TokenAmount::try_from(&coin)
+    }
+}
+
+impl TryFrom<&Coin> for TokenAmount {
+    type Error = eyre::Report;
+
+    fn try_from(coin: &Coin) -> Result<Self, Self::Error> {
+        let raw = coin
+            .amount
+            .parse()
+            .map_err(|e| eyre!("invalid coin amount {:?}: {e}", coin.amount))?;
+
+        Ok(Self::new(Denom::new(coin.denom.clone())?, raw))
+    }
+}
+
+impl From<TokenAmount> for Coin {
+    fn from(amount: TokenAmount) -> Self {
+        Coin {
+            denom: amount.denom.to_string(),
+            amount: amount.raw.to_string(),
+        }
+    }
+}
+
+/// Serializes base unit amounts as strings like Cosmos does, since JSON numbers can't hold a u128.
+/// Plain integers are accepted when deserializing. For use with `#[serde(with = "...")]` on `u128`
+/// fields.
+pub mod raw_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Int(u64),
+        Str(String),
+    }
+
+    pub fn serialize<S: Serializer>(raw: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&raw.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        match Raw::deserialize(deserializer)? {
+            Raw::Int(raw) => Ok(raw as u128),
+            Raw::Str(raw) => raw.parse().map_err(D::Error::custom),
+        }
+    }
+}
+
+/// For use with `#[serde(with = "sommelier_auction::amount::usomm")]` on a [`TokenAmount`] field
+/// that is always usomm, so it can be written as a plain integer amount in config files.
+pub mod usomm {
+    use serde::{Deserializer, Serializer};
+
+    use super::{raw_string, TokenAmount};
+
+    pub fn serialize<S: Serializer>(
+        amount: &TokenAmount,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if !amount.denom.is_usomm() {
+            return Err(serde::ser::Error::custom(format!(
+                "expected a usomm amount, got {amount}"
+            )));
+        }
+
+        match u64::try_from(amount.raw) {
+            Ok(raw) => serializer.serialize_u64(raw),
+            Err(_) => serializer.serialize_str(&amount.raw.to_string()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<TokenAmount, D::Error> {
+        raw_string::deserialize(deserializer).map(TokenAmount::usomm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_string_roundtrip() {
+        let amount = TokenAmount::from_decimal_str(Denom::WETH, "1.5", 18).unwrap();
+        assert_eq!(1_500_000_000_000_000_000, amount.raw);
+        assert_eq!("1.5", amount.to_decimal_string(18));
+
+        let amount =
+            TokenAmount::from_decimal_str(Denom::WETH, "0.000000000000000001", 18).unwrap();
+        assert_eq!(1, amount.raw);
+        assert_eq!("0.000000000000000001", amount.to_decimal_string(18));
+
+        let amount = TokenAmount::from_decimal_str(Denom::USOMM, "25", 6).unwrap();
+        assert_eq!(25_000_000, amount.raw);
+        assert_eq!("25", amount.to_decimal_string(6));
+
+        assert_eq!("0", TokenAmount::usomm(0).to_decimal_string(6));
+        assert_eq!(
+            Some("1.5".parse().unwrap()),
+            TokenAmount::new(Denom::USDC, 1_500_000).to_dec(6)
+        );
+        assert!(TokenAmount::from_decimal_str(Denom::USDC, "1.0000001", 6).is_err());
+        assert!(TokenAmount::from_decimal_str(Denom::USDC, "-1", 6).is_err());
+        assert!(TokenAmount::from_decimal_str(Denom::USDC, ".", 6).is_err());
+    }
+
+    #[test]
+    fn test_registry_parse_and_display() {
+        let registry = DenomRegistry::builtin();
+        let amount = TokenAmount::parse_with_registry("1.5 WETH", &registry).unwrap();
+
+        assert_eq!(Denom::WETH, amount.denom);
+        assert_eq!("1.5 WETH", amount.display_with(&registry));
+        assert!(TokenAmount::parse_with_registry("1.5 NOPE", &registry).is_err());
+    }
+
+    #[test]
+    fn test_checked_math() {
+        let a = TokenAmount::usomm(10);
+        let b = TokenAmount::usomm(3);
+
+        assert_eq!(13, a.checked_add(&b).unwrap().raw);
+        assert_eq!(7, a.checked_sub(&b).unwrap().raw);
+        assert!(b.checked_sub(&a).is_err());
+        assert_eq!(0, b.saturating_sub(&a).unwrap().raw);
+        assert!(a.checked_add(&TokenAmount::new(Denom::WETH, 1)).is_err());
+        assert!(TokenAmount::usomm(u128::MAX).checked_add(&b).is_err());
+    }
+
+    #[test]
+    fn test_coin_conversion() {
+        let amount: TokenAmount = "1000000usomm".parse().unwrap();
+        assert_eq!(TokenAmount::usomm(1_000_000), amount);
+        assert_eq!("1000000usomm", amount.to_string());
+
+        let coin = Coin::from(amount.clone());
+        assert_eq!(amount, TokenAmount::try_from(coin).unwrap());
+        assert!("usomm".parse::<TokenAmount>().is_err());
+        assert!("1000".parse::<TokenAmount>().is_err());
+    }
+
+    #[test]
+    fn test_serde() {
+        let amount = TokenAmount::new(Denom::WETH, 1_500_000_000_000_000_000_000_000);
+        let json = serde_json::to_string(&amount).unwrap();
+
+        assert_eq!(amount, serde_json::from_str(&json).unwrap());
+    }
+}
@@ -1,16 +1,82 @@
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 
-use crate::denom::Denom;
+use crate::{amount::TokenAmount, denom::Denom};
 
 /// Represents an order for one auction
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Bid {
     /// The ID of the auction to submit bid to
     pub auction_id: u32,
     /// The denomination of the token being auctioned
     pub fee_token: Denom,
     /// The most usomm (1,000,000 usomm = 1 SOMM) the bidder is willing to pay
-    pub maximum_usomm_in: u128,
-    /// The minimum units of fee token the bidder is willing to receive
-    pub minimum_tokens_out: u128,
+    pub maximum_usomm_in: TokenAmount,
+    /// The minimum amount of fee token the bidder is willing to receive
+    pub minimum_tokens_out: TokenAmount,
+}
+
+impl Bid {
+    /// The bid with its usomm offer cut to `maximum_usomm_in` and its minimum tokens out cut in
+    /// proportion. The minimum is rounded down, so the smaller bid may accept a slightly worse
+    /// price but can be filled at any price the original could. `None` if the smaller bid would
+    /// ask for nothing, or the offer wouldn't be smaller.
+    pub fn scaled_to(&self, maximum_usomm_in: u128) -> Option<Bid> {
+        let maximum = self.maximum_usomm_in.raw;
+        if maximum_usomm_in == 0 || maximum_usomm_in >= maximum {
+            return None;
+        }
+
+        let minimum_tokens_out =
+            self.minimum_tokens_out.raw.checked_mul(maximum_usomm_in)? / maximum;
+        if minimum_tokens_out == 0 {
+            return None;
+        }
+
+        Some(Bid {
+            maximum_usomm_in: TokenAmount::new(
+                self.maximum_usomm_in.denom.clone(),
+                maximum_usomm_in,
+            ),
+            minimum_tokens_out: TokenAmount::new(
+                self.minimum_tokens_out.denom.clone(),
+                minimum_tokens_out,
+            ),
+            ..self.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn bid() -> Bid {
+        fixtures::bid(1000, 300)
+    }
+
+    #[test]
+    fn test_scaled_to() {
+        let scaled = bid().scaled_to(500).unwrap();
+        assert_eq!(TokenAmount::usomm(500), scaled.maximum_usomm_in);
+        assert_eq!(
+            TokenAmount::new(Denom::USDC, 150),
+            scaled.minimum_tokens_out
+        );
+        assert_eq!(7, scaled.auction_id);
+
+        // rounds the minimum out down so the smaller bid can still be filled
+        let scaled = bid().scaled_to(333).unwrap();
+        assert_eq!(TokenAmount::new(Denom::USDC, 99), scaled.minimum_tokens_out);
+        let scaled = bid().scaled_to(334).unwrap();
+        assert_eq!(
+            TokenAmount::new(Denom::USDC, 100),
+            scaled.minimum_tokens_out
+        );
+        assert!(bid().scaled_to(3).is_none());
+
+        assert!(bid().scaled_to(0).is_none());
+        assert!(bid().scaled_to(1000).is_none());
+        assert!(bid().scaled_to(2000).is_none());
+    }
 }
@@ -1,12 +1,52 @@
-use std::str::FromStr;
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 
 use eyre::Result;
-use ocular::{cosmrs::Any, tx::UnsignedTx, MsgClient, QueryClient};
+use ocular::{
+    cosmrs::{
+        tx::{Fee, Raw},
+        Any,
+    },
+    tx::UnsignedTx,
+    MsgClient, QueryClient,
+};
 use prost::Message;
-use sommelier_auction_proto::cosmos_sdk_proto::cosmos::base::v1beta1::Coin;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sommelier_auction_proto::cosmos_sdk_proto::cosmos::{
+    bank::v1beta1::{
+        query_client::QueryClient as BankQueryClient, QueryAllBalancesRequest,
+        QueryDenomMetadataRequest,
+    },
+    base::{
+        tendermint::v1beta1::{
+            service_client::ServiceClient, GetBlockByHeightRequest, GetLatestBlockRequest,
+            GetNodeInfoRequest,
+        },
+        v1beta1::Coin,
+    },
+    tx::v1beta1::{service_client::ServiceClient as TxServiceClient, GetTxRequest},
+};
+use tracing::warn;
 
 use crate::{
-    auction::*, bid::Bid, cellarfees::*, denom::Denom, parameters::AuctionParameters, AccountInfo,
+    amount::TokenAmount,
+    auction::*,
+    bid::Bid,
+    cellarfees::*,
+    dec::Dec,
+    denom::{Denom, DenomTrace},
+    erc20::{Erc20Address, Erc20Mapping},
+    metadata::{DecimalsCheck, DenomMetadata},
+    parameters::AuctionParameters,
+    registry::{DenomInfo, DenomRegistry},
+    schedule::{upcoming_auctions, ScheduleInputs, UpcomingAuction, DEFAULT_BLOCK_TIME},
+    signer::{sign_tx, BidSigner},
+    validation::{BidValidationError, BidViolation},
+    version::{NodeInfo, VersionCheck, SUPPORTED_SOMMELIER_MAJOR_VERSION},
+    view::{BidView, TokenPriceQuote, TokenPriceView},
     BidResult,
 };
 
@@ -15,6 +55,52 @@ pub type TxSyncResponse = ocular::cosmrs::rpc::endpoint::broadcast::tx_sync::Res
 pub const DEFAULT_GRPC_ENDPOINT: &str = "https://sommelier-grpc.polkachu.com:14190";
 pub const DEFAULT_RPC_ENDPOINT: &str = "https://sommelier-rpc.polkachu.com:443";
 
+/// Gas limit of a bid transaction
+pub const BID_TX_GAS_LIMIT: u64 = 200_000;
+
+/// Gas price, in usomm per unit of gas, bid transactions pay unless the client is given another
+pub const DEFAULT_GAS_PRICE_USOMM: Dec = Dec::from_atomics(25_000_000_000_000_000);
+
+/// The transaction fee paid for submitting a bid at `gas_price_usomm`, in usomm. `None` if the gas
+/// price is negative or the fee overflows.
+pub fn bid_tx_fee_usomm(gas_price_usomm: Dec) -> Option<u128> {
+    gas_price_usomm.mul_int_ceil(BID_TX_GAS_LIMIT as u128)
+}
+
+/// Number of blocks after the latest one a bid transaction can be included in before it times out
+pub const BID_TX_TIMEOUT_BLOCKS: u64 = 100;
+
+/// The transaction a bid is sent in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BidTx {
+    /// Hash of the signed transaction in uppercase hex, as the chain reports it
+    pub hash: String,
+    /// The last height the transaction can be included at
+    pub timeout_height: u64,
+}
+
+/// A signed bid, ready to be sent with [`Client::broadcast_bid`]
+#[derive(Debug, Clone)]
+pub struct SignedBid {
+    pub bid: Bid,
+    pub tx: BidTx,
+    raw: Raw,
+}
+
+/// What the chain knows of the transaction a bid was sent in
+#[derive(Debug, Clone)]
+pub enum BidTxStatus {
+    /// The transaction isn't in a block. It can still be included up to its timeout height.
+    NotFound,
+    /// The transaction was included but failed, so the bid wasn't placed
+    Failed { code: u32, log: String },
+    /// The bid was placed
+    Placed(Box<BidResult>),
+}
+
+/// Number of blocks the average block time is measured over
+const BLOCK_TIME_SAMPLE_SIZE: u64 = 1000;
+
 #[derive(Debug, Clone)]
 /// A client for querying auction data and submitting bids.
 pub struct Client {
@@ -22,22 +108,85 @@ pub struct Client {
     grpc_endpoint: String,
     auction_client: crate::auction::query_client::QueryClient<tonic::transport::Channel>,
     cellarfees_client: crate::cellarfees::query_client::QueryClient<tonic::transport::Channel>,
+    tendermint_client: ServiceClient<tonic::transport::Channel>,
+    ibc_transfer_client: crate::ibc_transfer::query_client::QueryClient<tonic::transport::Channel>,
+    bank_client: BankQueryClient<tonic::transport::Channel>,
+    gravity_client: crate::gravity::query_client::QueryClient<tonic::transport::Channel>,
+    tx_client: TxServiceClient<tonic::transport::Channel>,
+    gas_price_usomm: Dec,
 }
 
 impl Client {
-    /// Construct a [`Client`] with the given endpoint
+    /// Construct a [`Client`] with the given endpoint. Logs a warning if the node runs an
+    /// unsupported Sommelier version.
     pub async fn with_endpoints(rpc: String, grpc: String) -> Result<Self> {
+        Self::with_endpoints_and_version_check(rpc, grpc, VersionCheck::Warn).await
+    }
+
+    /// Construct a [`Client`] with the given endpoint, checking the node's Sommelier version
+    /// according to `version_check`.
+    pub async fn with_endpoints_and_version_check(
+        rpc: String,
+        grpc: String,
+        version_check: VersionCheck,
+    ) -> Result<Self> {
         let auction_client =
             crate::auction::query_client::QueryClient::connect(grpc.clone()).await?;
         let cellarfees_client =
             crate::cellarfees::query_client::QueryClient::connect(grpc.clone()).await?;
-
-        Ok(Self {
+        let tendermint_client = ServiceClient::connect(grpc.clone()).await?;
+        let ibc_transfer_client =
+            crate::ibc_transfer::query_client::QueryClient::connect(grpc.clone()).await?;
+        let bank_client = BankQueryClient::connect(grpc.clone()).await?;
+        let gravity_client =
+            crate::gravity::query_client::QueryClient::connect(grpc.clone()).await?;
+        let tx_client = TxServiceClient::connect(grpc.clone()).await?;
+
+        let mut client = Self {
             rpc_endpoint: rpc,
             grpc_endpoint: grpc,
             auction_client,
             cellarfees_client,
-        })
+            tendermint_client,
+            ibc_transfer_client,
+            bank_client,
+            gravity_client,
+            tx_client,
+            gas_price_usomm: DEFAULT_GAS_PRICE_USOMM,
+        };
+
+        client.check_version(version_check).await?;
+
+        Ok(client)
+    }
+
+    /// Checks that the node runs a supported Sommelier major version. Depending on
+    /// `version_check`, a mismatch or a failure to determine the version is either logged or
+    /// returned as an error.
+    pub async fn check_version(&mut self, version_check: VersionCheck) -> Result<()> {
+        if version_check == VersionCheck::Skip {
+            return Ok(());
+        }
+
+        let message = match self.node_info().await {
+            Ok(info) if info.is_supported() => return Ok(()),
+            Ok(info) => format!(
+                "node at {} runs {} {}, but this crate supports Sommelier v{}. bids may be signed against a changed message format",
+                self.grpc_endpoint, info.app_name, info.version, SUPPORTED_SOMMELIER_MAJOR_VERSION
+            ),
+            Err(err) => format!(
+                "failed to determine the Sommelier version of node at {}: {err:?}",
+                self.grpc_endpoint
+            ),
+        };
+
+        if version_check == VersionCheck::Fail {
+            return Err(eyre::eyre!(message));
+        }
+
+        warn!("{message}");
+
+        Ok(())
     }
 
     /// Gets the current endpoint
@@ -45,6 +194,33 @@ impl Client {
         &self.grpc_endpoint
     }
 
+    /// Sets the gas price, in usomm per unit of gas, bid transactions pay. Fails if it's negative.
+    pub fn set_gas_price_usomm(&mut self, gas_price_usomm: Dec) -> Result<()> {
+        if gas_price_usomm.is_negative() {
+            return Err(eyre::eyre!(
+                "gas price of {gas_price_usomm} usomm is negative"
+            ));
+        }
+
+        self.gas_price_usomm = gas_price_usomm;
+
+        Ok(())
+    }
+
+    /// The transaction fee paid for submitting a bid, in usomm
+    pub fn bid_tx_fee_usomm(&self) -> u128 {
+        // the gas price is never negative, and can't be large enough to overflow
+        bid_tx_fee_usomm(self.gas_price_usomm).unwrap_or(u128::MAX)
+    }
+
+    /// Query the node's chain ID and application version
+    pub async fn node_info(&mut self) -> Result<NodeInfo> {
+        let request = GetNodeInfoRequest::default();
+        let response = self.tendermint_client.get_node_info(request).await?;
+
+        Ok(response.into_inner().into())
+    }
+
     /// Query all active auctions
     pub async fn active_auctions(&mut self) -> Result<Vec<Auction>> {
         let request = QueryActiveAuctionsRequest::default();
@@ -97,6 +273,67 @@ impl Client {
         Ok(response.into_inner().bids)
     }
 
+    /// Find the on-chain record of `bid` placed by `bidder`, if it landed, skipping the bids with
+    /// ids in `known`. Identical bids can't be told apart this way, so prefer
+    /// [`Client::bid_tx_status`] when the bid's transaction is known.
+    pub async fn find_bid(
+        &mut self,
+        bid: &Bid,
+        bidder: &str,
+        known: &[u64],
+    ) -> Result<Option<BidResult>> {
+        let bids = self.auction_bids(bid.auction_id).await?;
+
+        Ok(bids.into_iter().find(|result| {
+            !known.contains(&result.id)
+                && BidView::try_from(result).is_ok_and(|view| view.is_for(bid, bidder))
+        }))
+    }
+
+    /// Look up the transaction `bid` was sent in by `bidder`. Useful when a submission errored
+    /// without saying whether the bid was placed.
+    pub async fn bid_tx_status(
+        &mut self,
+        bid: &Bid,
+        tx: &BidTx,
+        bidder: &str,
+    ) -> Result<BidTxStatus> {
+        let request = GetTxRequest {
+            hash: tx.hash.clone(),
+        };
+        let response = match self.tx_client.get_tx(request).await {
+            Ok(response) => response.into_inner(),
+            Err(status) if is_tx_not_found(&status) => return Ok(BidTxStatus::NotFound),
+            Err(status) => return Err(status.into()),
+        };
+        let Some(tx_response) = response.tx_response else {
+            return Ok(BidTxStatus::NotFound);
+        };
+        if tx_response.code != 0 {
+            return Ok(BidTxStatus::Failed {
+                code: tx_response.code,
+                log: tx_response.raw_log,
+            });
+        }
+
+        // a transaction holds one bid, placed at the height it was included at
+        let height = u64::try_from(tx_response.height)?;
+        let bids = self.auction_bids(bid.auction_id).await?;
+        bids.into_iter()
+            .find(|result| {
+                result.block_height == height
+                    && BidView::try_from(result).is_ok_and(|view| view.is_for(bid, bidder))
+            })
+            .map(|result| BidTxStatus::Placed(Box::new(result)))
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "transaction {} was included at height {height} but its bid wasn't found in auction {}",
+                    tx.hash,
+                    bid.auction_id
+                )
+            })
+    }
+
     /// Query bid by bid ID and auction ID
     pub async fn auction_bid(&mut self, auction_id: u32, bid_id: u64) -> Result<BidResult> {
         let request = QueryBidRequest { auction_id, bid_id };
@@ -123,6 +360,135 @@ impl Client {
         Ok(response.into_inner().token_price.unwrap())
     }
 
+    /// Query token prices with typed fields, reporting how many blocks old each one is and
+    /// whether the chain would consider it stale
+    pub async fn token_price_quotes(&mut self) -> Result<Vec<TokenPriceQuote>> {
+        let price_max_block_age = self.auction_parameters().await?.price_max_block_age;
+        let (height, _) = self.block_height_and_time(None).await?;
+
+        self.token_prices()
+            .await?
+            .iter()
+            .map(|p| Ok(TokenPriceView::try_from(p)?.quote_at(height, price_max_block_age)))
+            .collect()
+    }
+
+    /// Query a token price by denom with typed fields and its freshness
+    pub async fn token_price_quote(&mut self, denom: Denom) -> Result<TokenPriceQuote> {
+        let price_max_block_age = self.auction_parameters().await?.price_max_block_age;
+        let (height, _) = self.block_height_and_time(None).await?;
+        let price = TokenPriceView::try_from(self.token_price(denom).await?)?;
+
+        Ok(price.quote_at(height, price_max_block_age))
+    }
+
+    /// Query the denom trace of an IBC denom
+    pub async fn denom_trace(&mut self, denom: &Denom) -> Result<DenomTrace> {
+        let hash = denom
+            .ibc_hash()
+            .ok_or_else(|| eyre::eyre!("{denom} is not an IBC denom"))?;
+        let request = crate::ibc_transfer::QueryDenomTraceRequest {
+            hash: hash.to_string(),
+        };
+        let response = self.ibc_transfer_client.denom_trace(request).await?;
+        let trace: DenomTrace = response
+            .into_inner()
+            .denom_trace
+            .ok_or_else(|| eyre::eyre!("no denom trace found for {denom}"))?
+            .into();
+
+        Ok(trace)
+    }
+
+    /// Check the registry's entry for an IBC denom against the denom's trace on chain, see
+    /// [`DenomRegistry::verify_ibc`]
+    pub async fn verify_ibc_denom(
+        &mut self,
+        registry: &mut DenomRegistry,
+        denom: &Denom,
+    ) -> Result<DenomInfo> {
+        let trace = self.denom_trace(denom).await?;
+
+        Ok(registry.verify_ibc(denom, trace)?.clone())
+    }
+
+    /// Query the ERC20 contract a denom is bridged to or from over the gravity bridge
+    pub async fn denom_to_erc20(&mut self, denom: &Denom) -> Result<Erc20Mapping> {
+        let request = crate::gravity::DenomToErc20Request {
+            denom: denom.to_string(),
+        };
+        let response = self
+            .gravity_client
+            .denom_to_erc20(request)
+            .await?
+            .into_inner();
+
+        Ok(Erc20Mapping {
+            denom: denom.clone(),
+            erc20: response.erc20.parse()?,
+            cosmos_originated: response.cosmos_originated,
+        })
+    }
+
+    /// Query the denom of an ERC20 contract bridged over the gravity bridge
+    pub async fn erc20_to_denom(&mut self, erc20: &Erc20Address) -> Result<Erc20Mapping> {
+        let request = crate::gravity::Erc20ToDenomRequest {
+            erc20: erc20.to_string(),
+        };
+        let response = self
+            .gravity_client
+            .erc20_to_denom(request)
+            .await?
+            .into_inner();
+
+        Ok(Erc20Mapping {
+            denom: response.denom.parse()?,
+            erc20: *erc20,
+            cosmos_originated: response.cosmos_originated,
+        })
+    }
+
+    /// Query a denom's metadata from the bank module
+    pub async fn denom_metadata(&mut self, denom: &Denom) -> Result<DenomMetadata> {
+        self.try_denom_metadata(denom)
+            .await?
+            .ok_or_else(|| eyre::eyre!("no metadata found for {denom}"))
+    }
+
+    // Returns None rather than an error if the chain has no metadata for the denom
+    async fn try_denom_metadata(&mut self, denom: &Denom) -> Result<Option<DenomMetadata>> {
+        let request = QueryDenomMetadataRequest {
+            denom: denom.to_string(),
+        };
+        match self.bank_client.denom_metadata(request).await {
+            Ok(response) => Ok(response.into_inner().metadata.map(Into::into)),
+            Err(status) if status.code() == tonic::Code::NotFound => Ok(None),
+            Err(status) => Err(status.into()),
+        }
+    }
+
+    /// Compare the registry's decimals for each denom with the bank module's metadata and the
+    /// auction module's token price exponent. Sources without data for a denom are skipped; a
+    /// denom with no chain data at all is reported as unverified rather than inconsistent.
+    pub async fn check_decimals(
+        &mut self,
+        registry: &DenomRegistry,
+        denoms: &[Denom],
+    ) -> Result<Vec<DecimalsCheck>> {
+        let token_prices = self.token_prices().await?;
+        let mut checks = Vec::new();
+
+        for denom in denoms {
+            let info = registry.resolve(denom)?;
+            let metadata = self.try_denom_metadata(denom).await?;
+            let token_price = token_prices.iter().find(|p| p.denom == denom.as_str());
+
+            checks.push(DecimalsCheck::new(info, metadata.as_ref(), token_price));
+        }
+
+        Ok(checks)
+    }
+
     /// Query auction interval. If the block height is a multiple of this value, the chain starts
     /// auctions for all qualifying fee denominations. Qualifying fee denominations have an accrual
     /// count of 2 or more.
@@ -144,7 +510,129 @@ impl Client {
         Ok(response.into_inner().fee_accrual_counters.unwrap().counters)
     }
 
-    /// Query auction parameters
+    /// Predict the next auction for each fee denom: whether it qualifies, the block and time it
+    /// is expected to start, and the balance of the cellarfees module account that would be
+    /// auctioned. The start time is extrapolated from the average block time over recent blocks.
+    pub async fn upcoming_auctions(&mut self) -> Result<Vec<UpcomingAuction>> {
+        let parameters = self.auction_parameters().await?;
+        let accruals = self
+            .fee_accruals()
+            .await?
+            .into_iter()
+            .map(|c| Ok((Denom::new(c.denom)?, c.count)))
+            .collect::<Result<Vec<(Denom, u64)>>>()?;
+        let active_auction_denoms = self
+            .active_auctions()
+            .await?
+            .iter()
+            .filter_map(|a| a.starting_tokens_for_sale.as_ref())
+            .map(|c| Denom::new(c.denom.clone()))
+            .collect::<eyre::Result<Vec<Denom>>>()?;
+        let fee_balances = self.fee_balances().await?;
+        let (height, time) = self.block_height_and_time(None).await?;
+        let block_time = self.average_block_time(height, time).await;
+
+        Ok(upcoming_auctions(&ScheduleInputs {
+            height,
+            time,
+            block_time,
+            parameters,
+            accruals,
+            fee_balances,
+            active_auction_denoms,
+        }))
+    }
+
+    /// Query the balances of the cellarfees module account, which holds the fees to be auctioned
+    pub async fn fee_balances(&mut self) -> Result<Vec<TokenAmount>> {
+        let request = crate::cellarfees::QueryModuleAccountsRequest::default();
+        let address = self
+            .cellarfees_client
+            .query_module_accounts(request)
+            .await?
+            .into_inner()
+            .fees_address;
+
+        self.balances(&address).await
+    }
+
+    /// Query all bank balances of an account
+    pub async fn balances(&mut self, address: &str) -> Result<Vec<TokenAmount>> {
+        let request = QueryAllBalancesRequest {
+            address: address.to_string(),
+            pagination: None,
+        };
+        let response = self.bank_client.all_balances(request).await?;
+
+        response
+            .into_inner()
+            .balances
+            .iter()
+            .map(TokenAmount::try_from)
+            .collect()
+    }
+
+    /// Query the height of the latest block
+    pub async fn latest_block_height(&mut self) -> Result<u64> {
+        Ok(self.block_height_and_time(None).await?.0)
+    }
+
+    /// The height and time of a block, or of the latest block if `height` is `None`
+    async fn block_height_and_time(&mut self, height: Option<u64>) -> Result<(u64, SystemTime)> {
+        let block = match height {
+            Some(height) => {
+                let request = GetBlockByHeightRequest {
+                    height: height as i64,
+                };
+                self.tendermint_client
+                    .get_block_by_height(request)
+                    .await?
+                    .into_inner()
+                    .block
+            }
+            None => {
+                let request = GetLatestBlockRequest::default();
+                self.tendermint_client
+                    .get_latest_block(request)
+                    .await?
+                    .into_inner()
+                    .block
+            }
+        };
+        let header = block
+            .and_then(|b| b.header)
+            .ok_or_else(|| eyre::eyre!("node returned a block without a header"))?;
+        let timestamp = header
+            .time
+            .ok_or_else(|| eyre::eyre!("block {} has no time", header.height))?;
+        let time = SystemTime::UNIX_EPOCH
+            + Duration::from_secs(u64::try_from(timestamp.seconds)?)
+            + Duration::from_nanos(u64::try_from(timestamp.nanos)?);
+
+        Ok((u64::try_from(header.height)?, time))
+    }
+
+    /// The average block time over the blocks before `height`, falling back to
+    /// [`DEFAULT_BLOCK_TIME`] if the earlier block can't be queried (e.g. it was pruned)
+    async fn average_block_time(&mut self, height: u64, time: SystemTime) -> Duration {
+        let blocks = BLOCK_TIME_SAMPLE_SIZE.min(height.saturating_sub(1));
+        if blocks == 0 {
+            return DEFAULT_BLOCK_TIME;
+        }
+
+        match self.block_height_and_time(Some(height - blocks)).await {
+            Ok((_, earlier)) => match time.duration_since(earlier) {
+                Ok(elapsed) => elapsed / blocks as u32,
+                Err(_) => DEFAULT_BLOCK_TIME,
+            },
+            Err(err) => {
+                warn!("failed to measure block time, using default: {err:?}");
+                DEFAULT_BLOCK_TIME
+            }
+        }
+    }
+
+    /// Query the parameters of the auction and cellarfees modules
     pub async fn auction_parameters(&mut self) -> Result<AuctionParameters> {
         let request = crate::auction::QueryParamsRequest::default();
         let ap = self
@@ -153,7 +641,7 @@ impl Client {
             .await?
             .into_inner()
             .params
-            .unwrap();
+            .ok_or_else(|| eyre::eyre!("auction module returned no params"))?;
 
         let request = crate::cellarfees::QueryParamsRequest::default();
         let cp = self
@@ -162,38 +650,88 @@ impl Client {
             .await?
             .into_inner()
             .params
-            .unwrap();
+            .ok_or_else(|| eyre::eyre!("cellarfees module returned no params"))?;
 
-        let auction_parameters = AuctionParameters {
-            auction_interval: cp.auction_interval,
-            fee_accrual_auction_threshold: cp.fee_accrual_auction_threshold,
-            price_decrease_block_interval: cp.price_decrease_block_interval,
-            initial_price_decrease_rate: cp.initial_price_decrease_rate.parse().unwrap(),
-            auction_price_decrease_acceleration_rate: ap
-                .auction_price_decrease_acceleration_rate
-                .parse()
-                .unwrap(),
-            minimum_bid_in_usomm: ap.minimum_bid_in_usomm,
-            minimum_sale_tokens_usd_value: ap.minimum_sale_tokens_usd_value.parse().unwrap(),
-        };
+        AuctionParameters::from_params(ap, cp)
+    }
+
+    /// Check a bid against its auction, the auction parameters and the on-chain token price of
+    /// the fee token. Returns every rule the bid breaks.
+    pub async fn validate_bid(&mut self, bid: &Bid) -> Result<Vec<BidViolation>> {
+        let auction = self.auction(bid.auction_id).await?;
+        let parameters = self.auction_parameters().await?;
+        let token_prices = self.token_prices().await?;
+
+        Ok(bid.validate(&auction, &parameters, &token_prices))
+    }
 
-        Ok(auction_parameters)
+    /// Submit a bid to an auction. The transaction is signed by `signer`, which may be a local
+    /// [`AccountInfo`] or any other [`BidSigner`] such as a [`crate::signer::RemoteSigner`].
+    ///
+    /// The bid is checked with [`Client::validate_bid`] first, and a [`BidValidationError`] is
+    /// returned instead of submitting it if it breaks any rules. Use
+    /// [`Client::submit_bid_unvalidated`] to skip the check.
+    pub async fn submit_bid<S>(&mut self, signer: &S, bid: Bid) -> Result<BidResult>
+    where
+        S: BidSigner + ?Sized,
+    {
+        let signed = self.sign_bid(signer, bid).await?;
+
+        self.broadcast_bid(&signed).await
     }
 
-    /// Submit a bid to an auction
-    pub async fn submit_bid(&mut self, sender: &AccountInfo, bid: Bid) -> Result<BidResult> {
+    /// Submit a bid to an auction without checking it against the auction's rules first
+    pub async fn submit_bid_unvalidated<S>(&mut self, signer: &S, bid: Bid) -> Result<BidResult>
+    where
+        S: BidSigner + ?Sized,
+    {
+        let signed = self.sign_bid_unvalidated(signer, bid).await?;
+
+        self.broadcast_bid(&signed).await
+    }
+
+    /// Sign a bid for [`Client::broadcast_bid`], checking it with [`Client::validate_bid`] first
+    /// like [`Client::submit_bid`] does. Signing first gives the bid's transaction hash, by which
+    /// it can be found with [`Client::bid_tx_status`] if broadcasting it errors.
+    pub async fn sign_bid<S>(&mut self, signer: &S, bid: Bid) -> Result<SignedBid>
+    where
+        S: BidSigner + ?Sized,
+    {
+        let violations = self.validate_bid(&bid).await?;
+        if !violations.is_empty() {
+            return Err(BidValidationError { violations }.into());
+        }
+
+        self.sign_bid_unvalidated(signer, bid).await
+    }
+
+    /// Sign a bid without checking it against the auction's rules first. The transaction times
+    /// out [`BID_TX_TIMEOUT_BLOCKS`] blocks after the latest one.
+    pub async fn sign_bid_unvalidated<S>(&mut self, signer: &S, bid: Bid) -> Result<SignedBid>
+    where
+        S: BidSigner + ?Sized,
+    {
+        if !bid.maximum_usomm_in.denom.is_usomm() {
+            return Err(eyre::eyre!(
+                "maximum bid must be denominated in usomm, got {}",
+                bid.maximum_usomm_in
+            ));
+        }
+        if bid.minimum_tokens_out.denom != bid.fee_token {
+            return Err(eyre::eyre!(
+                "minimum tokens out {} does not match fee token {}",
+                bid.minimum_tokens_out,
+                bid.fee_token
+            ));
+        }
+
         let mut unsigned_tx = UnsignedTx::new();
+        let signer_address = signer.address("somm").await?;
         let request = MsgSubmitBidRequest {
             auction_id: bid.auction_id,
-            signer: sender.address("somm")?,
-            max_bid_in_usomm: Some(Coin {
-                amount: bid.maximum_usomm_in.to_string(),
-                denom: "usomm".to_string(),
-            }),
-            sale_token_minimum_amount: Some(Coin {
-                amount: bid.minimum_tokens_out.to_string(),
-                denom: bid.fee_token.to_string(),
-            }),
+            signer: signer_address.clone(),
+            max_bid_in_usomm: Some(Coin::from(bid.maximum_usomm_in.clone())),
+            sale_token_minimum_amount: Some(Coin::from(bid.minimum_tokens_out.clone())),
         };
 
         // most of this is just getting things into a form ocular's API will accept.
@@ -207,20 +745,47 @@ impl Client {
         };
         unsigned_tx.add_msg(any);
 
+        let timeout_height = self.latest_block_height().await? + BID_TX_TIMEOUT_BLOCKS;
+        unsigned_tx.timeout_height(u32::try_from(timeout_height)?);
+
         let mut q_client = QueryClient::new(&self.grpc_endpoint)?;
-        let fee_info = ocular::prelude::FeeInfo::new(ocular::cosmrs::Coin {
-            amount: 0,
-            denom: ocular::cosmrs::Denom::from_str("usomm")?,
-        });
+        let account = q_client.account(&signer_address).await?;
+        let fee = Fee::from_amount_and_gas(
+            ocular::cosmrs::Coin {
+                amount: self.bid_tx_fee_usomm(),
+                denom: ocular::cosmrs::Denom::from_str("usomm")?,
+            },
+            BID_TX_GAS_LIMIT,
+        );
         let chain_context = ocular::chain::ChainContext {
             id: "sommelier-3".to_string(),
             prefix: "somm".to_string(),
         };
-        let signed_tx = unsigned_tx
-            .sign(sender, fee_info, &chain_context, &mut q_client)
-            .await?;
-        let mut m_client = MsgClient::new(&self.rpc_endpoint)?;
-        let response = signed_tx.broadcast_commit(&mut m_client).await?;
+        let raw = sign_tx(
+            signer,
+            unsigned_tx,
+            fee,
+            &chain_context,
+            account.account_number,
+            account.sequence,
+        )
+        .await?;
+
+        Ok(SignedBid {
+            bid,
+            tx: BidTx {
+                hash: tx_hash(&raw.to_bytes()?),
+                timeout_height,
+            },
+            raw,
+        })
+    }
+
+    /// Broadcast a signed bid and wait for it to be committed. If this errors without the chain
+    /// saying whether the bid was placed, look it up with [`Client::bid_tx_status`].
+    pub async fn broadcast_bid(&self, signed: &SignedBid) -> Result<BidResult> {
+        let m_client = MsgClient::new(&self.rpc_endpoint)?;
+        let response = signed.raw.broadcast_commit(m_client.inner()).await?;
 
         if response.deliver_tx.code.value() != 0 {
             return Err(eyre::eyre!(
@@ -247,3 +812,35 @@ impl Client {
         Err(eyre::eyre!("no data in response: {:?}", response))
     }
 }
+
+// The hash the chain identifies a transaction by
+fn tx_hash(tx_bytes: &[u8]) -> String {
+    hex::encode_upper(Sha256::digest(tx_bytes))
+}
+
+// Nodes answer lookups of unknown transactions with NotFound, or with a generic error on some
+// versions of the SDK
+fn is_tx_not_found(status: &tonic::Status) -> bool {
+    status.code() == tonic::Code::NotFound || status.message().contains("not found")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tx_hash() {
+        assert_eq!(
+            tx_hash(b""),
+            "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855"
+        );
+    }
+
+    #[test]
+    fn test_bid_tx_fee() {
+        assert_eq!(bid_tx_fee_usomm(DEFAULT_GAS_PRICE_USOMM), Some(5000));
+        assert_eq!(bid_tx_fee_usomm(Dec::ZERO), Some(0));
+        assert_eq!(bid_tx_fee_usomm("0.0000001".parse().unwrap()), Some(1));
+        assert_eq!(bid_tx_fee_usomm("-0.025".parse().unwrap()), None);
+    }
+}
@@ -0,0 +1,449 @@
+//! An exact fixed-point decimal matching the Cosmos SDK's `sdk.Dec`, used for the prices and rates
+//! the auction module reports as strings.
+use std::{fmt::Display, str::FromStr};
+
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::auction::{Auction, Bid as ProtoBid, TokenPrice};
+
+/// 10^18, the scale of [`Dec`] atomics
+const PRECISION_MULTIPLIER: i128 = 1_000_000_000_000_000_000;
+
+/// A signed decimal with 18 fractional digits, stored as an integer number of 10^-18 units
+/// ("atomics") the same way `sdk.Dec` is.
+///
+/// `sdk.Dec` has two string forms. Protobuf serializes the atomics as a plain integer, i.e.
+/// "1500000000000000000" for 1.5, while JSON and `String()` use the human form
+/// "1.500000000000000000". [`Dec::from_wire`] accepts both; [`FromStr`] only accepts the human form.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct Dec(i128);
+
+impl Dec {
+    /// The number of fractional digits
+    pub const PRECISION: u32 = 18;
+    pub const ZERO: Dec = Dec(0);
+    pub const ONE: Dec = Dec(PRECISION_MULTIPLIER);
+
+    /// Constructs a decimal from its atomics, the value times 10^18
+    pub const fn from_atomics(atomics: i128) -> Self {
+        Self(atomics)
+    }
+
+    /// Constructs a whole number decimal
+    pub fn from_int(value: i128) -> Option<Self> {
+        Some(Self(value.checked_mul(PRECISION_MULTIPLIER)?))
+    }
+
+    /// Parses a decimal string as found in auction and cellarfees protos. Strings containing a
+    /// decimal point are read in the human form, anything else as integer atomics.
+    pub fn from_wire(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.contains('.') {
+            return s.parse();
+        }
+
+        let (negative, digits) = split_sign(s);
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(eyre!("invalid decimal {s:?}: expected integer atomics"));
+        }
+
+        let atomics = digits
+            .parse::<i128>()
+            .map_err(|_| eyre!("invalid decimal {s:?}: out of range"))?;
+
+        Ok(Self(if negative { -atomics } else { atomics }))
+    }
+
+    /// The value times 10^18
+    pub fn atomics(&self) -> i128 {
+        self.0
+    }
+
+    /// The integer atomics string protobuf uses for `sdk.Dec`
+    pub fn to_wire_string(&self) -> String {
+        self.0.to_string()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.0 > 0
+    }
+
+    /// The value as a float. Only for display and rough valuation.
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / PRECISION_MULTIPLIER as f64
+    }
+
+    /// The integer part, rounding toward zero
+    pub fn truncate(&self) -> i128 {
+        self.0 / PRECISION_MULTIPLIER
+    }
+
+    /// The smallest integer greater than or equal to the value
+    pub fn ceil(&self) -> i128 {
+        let truncated = self.truncate();
+        if self.0 % PRECISION_MULTIPLIER > 0 {
+            truncated + 1
+        } else {
+            truncated
+        }
+    }
+
+    pub fn checked_add(&self, other: Dec) -> Option<Self> {
+        Some(Self(self.0.checked_add(other.0)?))
+    }
+
+    pub fn checked_sub(&self, other: Dec) -> Option<Self> {
+        Some(Self(self.0.checked_sub(other.0)?))
+    }
+
+    /// Multiplies, rounding half to even like `sdk.Dec.Mul`
+    pub fn checked_mul(&self, other: Dec) -> Option<Self> {
+        let negative = (self.0 < 0) != (other.0 < 0);
+        let (a, b) = (self.0.checked_abs()?, other.0.checked_abs()?);
+        let (a_int, a_frac) = (a / PRECISION_MULTIPLIER, a % PRECISION_MULTIPLIER);
+        let (b_int, b_frac) = (b / PRECISION_MULTIPLIER, b % PRECISION_MULTIPLIER);
+
+        // (a_int + a_frac) * (b_int + b_frac) expanded so no term overflows before scaling down
+        let frac_product = a_frac * b_frac;
+        let mut atomics = a_int
+            .checked_mul(b)?
+            .checked_add(a_frac.checked_mul(b_int)?)?
+            .checked_add(frac_product / PRECISION_MULTIPLIER)?;
+        if round_up(
+            frac_product % PRECISION_MULTIPLIER,
+            PRECISION_MULTIPLIER,
+            atomics,
+        ) {
+            atomics = atomics.checked_add(1)?;
+        }
+
+        Some(Self(if negative { -atomics } else { atomics }))
+    }
+
+    /// Divides, rounding half to even like `sdk.Dec.Quo`. Returns `None` when dividing by zero.
+    pub fn checked_div(&self, other: Dec) -> Option<Self> {
+        if other.0 == 0 {
+            return None;
+        }
+
+        let negative = (self.0 < 0) != (other.0 < 0);
+        let (a, b) = (self.0.checked_abs()?, other.0.checked_abs()?);
+
+        // long division, one decimal digit at a time, so a * 10^18 never has to fit in an i128
+        let mut atomics = a / b;
+        let mut remainder = a % b;
+        for _ in 0..Self::PRECISION {
+            remainder = remainder.checked_mul(10)?;
+            atomics = atomics.checked_mul(10)?.checked_add(remainder / b)?;
+            remainder %= b;
+        }
+        if round_up(remainder, b, atomics) {
+            atomics = atomics.checked_add(1)?;
+        }
+
+        Some(Self(if negative { -atomics } else { atomics }))
+    }
+
+    /// `amount * self` for an integer amount, rounded down. `None` if negative or on overflow.
+    pub fn mul_int_floor(&self, amount: u128) -> Option<u128> {
+        let value = u128::try_from(self.0).ok()?;
+        let scale = PRECISION_MULTIPLIER as u128;
+        let (value_int, value_frac) = (value / scale, value % scale);
+        let (amount_int, amount_frac) = (amount / scale, amount % scale);
+
+        amount
+            .checked_mul(value_int)?
+            .checked_add(amount_int.checked_mul(value_frac)?)?
+            .checked_add(amount_frac * value_frac / scale)
+    }
+
+    /// `amount * self` for an integer amount, rounded up. `None` if negative or on overflow.
+    pub fn mul_int_ceil(&self, amount: u128) -> Option<u128> {
+        let floor = self.mul_int_floor(amount)?;
+        let scale = PRECISION_MULTIPLIER as u128;
+        let exact = (amount % scale) * (self.0 as u128 % scale) % scale == 0;
+
+        if exact {
+            Some(floor)
+        } else {
+            floor.checked_add(1)
+        }
+    }
+
+    /// `amount / self` for an integer amount, rounded down. `None` if `self` isn't positive or on
+    /// overflow.
+    pub fn div_int_floor(&self, amount: u128) -> Option<u128> {
+        if self.0 <= 0 {
+            return None;
+        }
+
+        amount
+            .checked_mul(PRECISION_MULTIPLIER as u128)?
+            .checked_div(self.0 as u128)
+    }
+}
+
+/// Whether a quotient with `remainder / divisor` left over rounds up, using round half to even
+fn round_up(remainder: i128, divisor: i128, quotient: i128) -> bool {
+    let rest = divisor - remainder;
+
+    remainder > rest || (remainder == rest && quotient % 2 == 1)
+}
+
+fn split_sign(s: &str) -> (bool, &str) {
+    match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    }
+}
+
+/// Parses the human form, i.e. "1.5", "-0.25" or "3"
+impl FromStr for Dec {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (negative, unsigned) = split_sign(s);
+        let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+        if whole.is_empty() || (unsigned.contains('.') && fraction.is_empty()) {
+            return Err(eyre!("invalid decimal {s:?}"));
+        }
+        if !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return Err(eyre!("invalid decimal {s:?}: unexpected character"));
+        }
+        if fraction.len() > Self::PRECISION as usize {
+            return Err(eyre!(
+                "invalid decimal {s:?}: more than {} fractional digits",
+                Self::PRECISION
+            ));
+        }
+
+        let digits = format!(
+            "{whole}{fraction:0<width$}",
+            width = Self::PRECISION as usize
+        );
+        let atomics = digits
+            .parse::<i128>()
+            .map_err(|_| eyre!("invalid decimal {s:?}: out of range"))?;
+
+        Ok(Self(if negative { -atomics } else { atomics }))
+    }
+}
+
+/// Displays the human form with all 18 fractional digits, like `sdk.Dec.String()`
+impl Display for Dec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let atomics = self.0.unsigned_abs();
+        let scale = PRECISION_MULTIPLIER as u128;
+
+        write!(
+            f,
+            "{sign}{}.{:0>width$}",
+            atomics / scale,
+            atomics % scale,
+            width = Self::PRECISION as usize
+        )
+    }
+}
+
+impl TryFrom<String> for Dec {
+    type Error = eyre::Report;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Dec> for String {
+    fn from(dec: Dec) -> String {
+        dec.to_string()
+    }
+}
+
+/// Typed access to the decimal fields of [`Auction`]
+pub trait AuctionDecExt {
+    /// The price in usomm of one base unit of the token for sale when the auction started
+    fn initial_unit_price(&self) -> Result<Dec>;
+    /// The current price in usomm of one base unit of the token for sale
+    fn current_unit_price(&self) -> Result<Dec>;
+    fn initial_decrease_rate(&self) -> Result<Dec>;
+    fn current_decrease_rate(&self) -> Result<Dec>;
+}
+
+impl AuctionDecExt for Auction {
+    fn initial_unit_price(&self) -> Result<Dec> {
+        parse_field(
+            &self.initial_unit_price_in_usomm,
+            "initial_unit_price_in_usomm",
+        )
+    }
+
+    fn current_unit_price(&self) -> Result<Dec> {
+        parse_field(
+            &self.current_unit_price_in_usomm,
+            "current_unit_price_in_usomm",
+        )
+    }
+
+    fn initial_decrease_rate(&self) -> Result<Dec> {
+        parse_field(
+            &self.initial_price_decrease_rate,
+            "initial_price_decrease_rate",
+        )
+    }
+
+    fn current_decrease_rate(&self) -> Result<Dec> {
+        parse_field(
+            &self.current_price_decrease_rate,
+            "current_price_decrease_rate",
+        )
+    }
+}
+
+/// Typed access to the decimal fields of an on-chain [`ProtoBid`]
+pub trait BidDecExt {
+    /// The price in usomm per base unit of the sale token the bid was filled at
+    fn sale_token_unit_price(&self) -> Result<Dec>;
+}
+
+impl BidDecExt for ProtoBid {
+    fn sale_token_unit_price(&self) -> Result<Dec> {
+        parse_field(
+            &self.sale_token_unit_price_in_usomm,
+            "sale_token_unit_price_in_usomm",
+        )
+    }
+}
+
+/// Typed access to the decimal fields of [`TokenPrice`]
+pub trait TokenPriceDecExt {
+    /// The USD price of one whole token (10^exponent base units)
+    fn usd_price_dec(&self) -> Result<Dec>;
+}
+
+impl TokenPriceDecExt for TokenPrice {
+    fn usd_price_dec(&self) -> Result<Dec> {
+        parse_field(&self.usd_price, "usd_price")
+    }
+}
+
+pub(crate) fn parse_field(value: &str, field: &str) -> Result<Dec> {
+    Dec::from_wire(value).map_err(|err| eyre!("failed to parse {field}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Dec {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Dec::from_atomics(1_500_000_000_000_000_000), dec("1.5"));
+        assert_eq!(dec("1.5"), Dec::from_wire("1500000000000000000").unwrap());
+        assert_eq!(dec("1.5"), Dec::from_wire("1.500000000000000000").unwrap());
+        assert_eq!(Dec::from_atomics(1), Dec::from_wire("1").unwrap());
+        assert_eq!(dec("-0.25"), Dec::from_wire("-250000000000000000").unwrap());
+        assert_eq!(Dec::from_int(3).unwrap(), dec("3"));
+
+        assert!("".parse::<Dec>().is_err());
+        assert!("1.".parse::<Dec>().is_err());
+        assert!(".5".parse::<Dec>().is_err());
+        assert!("1.5e3".parse::<Dec>().is_err());
+        assert!("0.0000000000000000001".parse::<Dec>().is_err());
+        assert!(Dec::from_wire("abc").is_err());
+        assert!(Dec::from_wire("").is_err());
+        assert!(Dec::from_wire("1e18").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("1.500000000000000000", dec("1.5").to_string());
+        assert_eq!("-0.000000000000000001", Dec::from_atomics(-1).to_string());
+        assert_eq!("1500000000000000000", dec("1.5").to_wire_string());
+
+        let json = serde_json::to_string(&dec("2.25")).unwrap();
+        assert_eq!(dec("2.25"), serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn test_math() {
+        assert_eq!(dec("3.75"), dec("1.5").checked_mul(dec("2.5")).unwrap());
+        assert_eq!(dec("-3.75"), dec("-1.5").checked_mul(dec("2.5")).unwrap());
+        assert_eq!(
+            dec("1000000000000"),
+            dec("1000000").checked_mul(dec("1000000")).unwrap()
+        );
+        assert_eq!(dec("0.6"), dec("1.5").checked_div(dec("2.5")).unwrap());
+        assert_eq!(
+            dec("0.333333333333333333"),
+            Dec::ONE.checked_div(dec("3")).unwrap()
+        );
+        assert_eq!(
+            dec("0.666666666666666667"),
+            dec("2").checked_div(dec("3")).unwrap()
+        );
+        assert!(Dec::ONE.checked_div(Dec::ZERO).is_none());
+        assert_eq!(2, dec("1.5").ceil());
+        assert_eq!(-1, dec("-1.5").ceil());
+        assert_eq!(3, dec("3").ceil());
+        assert!(Dec::from_atomics(i128::MAX)
+            .checked_add(Dec::from_atomics(1))
+            .is_none());
+    }
+
+    #[test]
+    fn test_int_math() {
+        // 0.000002 usomm per wei
+        let price = dec("0.000002");
+
+        assert_eq!(
+            Some(5_000_000_000_000_000_000),
+            price.div_int_floor(10_000_000_000_000)
+        );
+        assert_eq!(
+            Some(10_000_000_000_000),
+            price.mul_int_floor(5_000_000_000_000_000_000)
+        );
+        assert_eq!(Some(3), dec("1.5").mul_int_floor(2));
+        assert_eq!(Some(3), dec("1.5").mul_int_ceil(2));
+        assert_eq!(Some(1), dec("1.5").mul_int_floor(1));
+        assert_eq!(Some(2), dec("1.5").mul_int_ceil(1));
+        assert_eq!(Some(4), dec("1.25").mul_int_ceil(3));
+        assert_eq!(Some(1), dec("1.5").div_int_floor(2));
+        assert_eq!(None, Dec::ZERO.div_int_floor(2));
+        assert_eq!(None, dec("-1").mul_int_floor(2));
+    }
+
+    #[test]
+    fn test_auction_accessors() {
+        let auction = Auction {
+            current_unit_price_in_usomm: "2000000000000000000".to_string(),
+            initial_unit_price_in_usomm: "not a number".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(dec("2"), auction.current_unit_price().unwrap());
+        assert!(auction.initial_unit_price().is_err());
+    }
+}
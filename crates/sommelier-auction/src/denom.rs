@@ -1,80 +1,225 @@
-use std::{fmt::Display, str::FromStr};
+use std::{borrow::Cow, fmt::Display, str::FromStr};
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
-/// Represents a valid denomination on the Sommelier blockchain. Provides convenience methods for
-/// mapping between formats and getting decimals.
-pub enum Denom {
-    #[default]
-    /// Should not be used. Necessary for handling default deserializiation values.
-    EMPTY,
-    #[serde(rename = "gravity0x6B175474E89094C44Da98b954EedeAC495271d0F")]
-    DAI,
-    #[serde(rename = "gravity0x853d955aCEf822Db058eb8505911ED77F175b99e")]
-    FRAX,
-    #[serde(rename = "usomm")]
-    USOMM,
-    #[serde(rename = "gravity0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")]
-    USDC,
-    #[serde(rename = "gravity0xdAC17F958D2ee523a2206206994597C13D831ec7")]
-    USDT,
-    #[serde(rename = "gravity0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599")]
-    WBTC,
-    #[serde(rename = "gravity0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")]
-    WETH,
-    #[serde(rename = "gravity0xd35CCeEAD182dcee0F148EbaC9447DA2c4D449c4")]
-    GoerliUSDC,
-    #[serde(rename = "gravity0xB4FBF271143F4FBf7B91A5ded31805e42b2208d6")]
-    GoerliWETH,
-}
+use crate::{
+    cosmos_sdk_proto::ibc::applications::transfer::v1::DenomTrace as ProtoDenomTrace,
+    erc20::{Erc20Address, EthereumChain},
+};
+
+const IBC_PREFIX: &str = "ibc/";
+const GRAVITY_PREFIX: &str = "gravity";
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+/// Represents a valid denomination on the Sommelier blockchain. Any denom accepted by the chain
+/// can be represented; metadata such as decimals and symbols lives in a
+/// [`crate::registry::DenomRegistry`]. Well known denoms are available as associated constants.
+pub struct Denom(Cow<'static, str>);
 
 impl Denom {
-    pub fn decimals(&self) -> u8 {
-        match self {
-            Denom::DAI => 18,
-            Denom::FRAX => 18,
-            Denom::USOMM => 6,
-            Denom::USDC => 6,
-            Denom::USDT => 6,
-            Denom::WBTC => 8,
-            Denom::WETH => 18,
-            Denom::EMPTY => 0,
-            Denom::GoerliUSDC => 6,
-            Denom::GoerliWETH => 18,
+    pub const USOMM: Denom = Denom::from_static("usomm");
+    pub const DAI: Denom = Denom::from_static("gravity0x6B175474E89094C44Da98b954EedeAC495271d0F");
+    pub const FRAX: Denom = Denom::from_static("gravity0x853d955aCEf822Db058eb8505911ED77F175b99e");
+    pub const USDC: Denom = Denom::from_static("gravity0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+    pub const USDT: Denom = Denom::from_static("gravity0xdAC17F958D2ee523a2206206994597C13D831ec7");
+    pub const WBTC: Denom = Denom::from_static("gravity0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599");
+    pub const WETH: Denom = Denom::from_static("gravity0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+    pub const GOERLI_USDC: Denom =
+        Denom::from_static("gravity0xd35CCeEAD182dcee0F148EbaC9447DA2c4D449c4");
+    pub const GOERLI_WETH: Denom =
+        Denom::from_static("gravity0xB4FBF271143F4FBf7B91A5ded31805e42b2208d6");
+
+    /// Construct a [`Denom`], validating it against the Cosmos SDK denom rules. IBC denoms must
+    /// be of the form `ibc/<hash>` where the hash is a 32 byte hex string, which is normalized to
+    /// upper case. A bare `0x...` ERC20 address is converted to its gravity denom, and gravity
+    /// denoms are normalized to checksummed addresses.
+    pub fn new(denom: impl Into<String>) -> eyre::Result<Self> {
+        let mut denom = denom.into();
+        if denom.starts_with("0x") || denom.starts_with("0X") {
+            return Ok(Denom::from_erc20(&denom.parse()?));
+        }
+
+        validate_denom(&denom)?;
+
+        if let Some(address) = denom.strip_prefix(GRAVITY_PREFIX) {
+            if let Ok(address) = address.parse::<Erc20Address>() {
+                return Ok(Denom::from_erc20(&address));
+            }
+        }
+
+        if let Some(hash) = denom.strip_prefix(IBC_PREFIX) {
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(eyre::eyre!(
+                    "invalid IBC denom {denom:?}. expected ibc/ followed by a 64 character hex hash"
+                ));
+            }
+
+            denom = format!("{IBC_PREFIX}{}", hash.to_ascii_uppercase());
         }
+
+        Ok(Denom(Cow::Owned(denom)))
+    }
+
+    /// Construct the gravity denom of an ERC20 token bridged from Ethereum
+    pub fn from_erc20(address: &Erc20Address) -> Self {
+        Denom(Cow::Owned(format!("{GRAVITY_PREFIX}{address}")))
+    }
+
+    /// Construct a [`Denom`] from a static string without validation
+    const fn from_static(denom: &'static str) -> Self {
+        Denom(Cow::Borrowed(denom))
+    }
+
+    /// Gets the on-chain denom string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this is the chain's native usomm denom
+    pub fn is_usomm(&self) -> bool {
+        *self == Denom::USOMM
+    }
+
+    /// Whether this is a token bridged from Ethereum over the gravity bridge
+    pub fn is_gravity(&self) -> bool {
+        self.erc20_address().is_some()
+    }
+
+    /// Gets the ERC20 contract address of a gravity denom
+    pub fn erc20_address(&self) -> Option<Erc20Address> {
+        self.0.strip_prefix(GRAVITY_PREFIX)?.parse().ok()
     }
 
-    pub fn symbol(&self) -> String {
-        match self {
-            Denom::DAI => "DAI".to_string(),
-            Denom::FRAX => "FRAX".to_string(),
-            Denom::USOMM => "USOMM".to_string(),
-            Denom::USDC => "USDC".to_string(),
-            Denom::USDT => "USDT".to_string(),
-            Denom::WBTC => "WBTC".to_string(),
-            Denom::WETH => "WETH".to_string(),
-            Denom::EMPTY => "".to_string(),
-            Denom::GoerliUSDC => "GoerliUSDC".to_string(),
-            Denom::GoerliWETH => "GoerliWETH".to_string(),
+    /// Gets the Ethereum network a gravity denom is bridged from. Sommelier's gravity bridge
+    /// connects to mainnet; the Goerli test tokens are the only exception.
+    pub fn ethereum_chain(&self) -> Option<EthereumChain> {
+        self.erc20_address()?;
+
+        if *self == Denom::GOERLI_USDC || *self == Denom::GOERLI_WETH {
+            return Some(EthereumChain::Goerli);
         }
+
+        Some(EthereumChain::Mainnet)
+    }
+
+    /// Whether this is an IBC voucher denom, such as a token bridged over Axelar
+    pub fn is_ibc(&self) -> bool {
+        self.0.starts_with(IBC_PREFIX)
+    }
+
+    /// Gets the hex encoded trace hash of an IBC denom
+    pub fn ibc_hash(&self) -> Option<&str> {
+        self.0.strip_prefix(IBC_PREFIX)
     }
 }
 
+/// The origin of an IBC voucher denom: the port/channel hops it took to reach Sommelier and its
+/// denom on the source chain.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DenomTrace {
+    /// Port and channel identifiers, i.e. "transfer/channel-5". Empty for native denoms.
+    pub path: String,
+    /// The denom on the source chain, i.e. "uusdc"
+    pub base_denom: String,
+}
+
+impl DenomTrace {
+    pub fn new(path: impl Into<String>, base_denom: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            base_denom: base_denom.into(),
+        }
+    }
+
+    /// The full path, i.e. "transfer/channel-5/uusdc"
+    pub fn full_path(&self) -> String {
+        if self.path.is_empty() {
+            return self.base_denom.clone();
+        }
+
+        format!("{}/{}", self.path, self.base_denom)
+    }
+
+    /// The (port, channel) hops in the path
+    pub fn hops(&self) -> Vec<(&str, &str)> {
+        let parts: Vec<&str> = self.path.split('/').filter(|p| !p.is_empty()).collect();
+
+        parts
+            .chunks(2)
+            .filter_map(|c| Some((c[0], *c.get(1)?)))
+            .collect()
+    }
+
+    /// The denom this trace produces on the receiving chain: `ibc/<sha256(full path)>`, or the
+    /// base denom itself when the path is empty.
+    pub fn ibc_denom(&self) -> eyre::Result<Denom> {
+        if self.path.is_empty() {
+            return Denom::new(self.base_denom.clone());
+        }
+
+        let hash = Sha256::digest(self.full_path().as_bytes());
+
+        Denom::new(format!("{IBC_PREFIX}{}", hex::encode_upper(hash)))
+    }
+}
+
+impl FromStr for DenomTrace {
+    type Err = eyre::Report;
+
+    /// Parses a full path such as "transfer/channel-5/uusdc". Leading port/channel pairs make up
+    /// the path and the remainder is the base denom.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('/').collect();
+        let mut hops = 0;
+        while parts.len() > hops * 2 + 2 && parts[hops * 2 + 1].starts_with("channel-") {
+            hops += 1;
+        }
+
+        let base_denom = parts[hops * 2..].join("/");
+        if base_denom.is_empty() {
+            return Err(eyre::eyre!("invalid denom trace {s:?}"));
+        }
+
+        Ok(DenomTrace::new(parts[..hops * 2].join("/"), base_denom))
+    }
+}
+
+impl Display for DenomTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.full_path())
+    }
+}
+
+impl From<ProtoDenomTrace> for DenomTrace {
+    fn from(trace: ProtoDenomTrace) -> Self {
+        DenomTrace::new(trace.path, trace.base_denom)
+    }
+}
+
+/// Checks a denom against the Cosmos SDK's `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}` rule
+fn validate_denom(denom: &str) -> eyre::Result<()> {
+    let mut chars = denom.chars();
+    let valid_first = chars.next().is_some_and(|c| c.is_ascii_alphabetic());
+    let valid_rest = chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+
+    if !valid_first || !valid_rest || !(3..=128).contains(&denom.len()) {
+        return Err(eyre::eyre!("invalid denom {denom:?}"));
+    }
+
+    Ok(())
+}
+
 impl Display for Denom {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Denom::DAI => write!(f, "gravity0x6B175474E89094C44Da98b954EedeAC495271d0F"),
-            Denom::FRAX => write!(f, "gravity0x853d955aCEf822Db058eb8505911ED77F175b99e"),
-            Denom::USOMM => write!(f, "usomm"),
-            Denom::USDC => write!(f, "gravity0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
-            Denom::USDT => write!(f, "gravity0xdAC17F958D2ee523a2206206994597C13D831ec7"),
-            Denom::WBTC => write!(f, "gravity0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599"),
-            Denom::WETH => write!(f, "gravity0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
-            Denom::EMPTY => Err(std::fmt::Error),
-            Denom::GoerliUSDC => write!(f, "gravity0xd35CCeEAD182dcee0F148EbaC9447DA2c4D449c4"),
-            Denom::GoerliWETH => write!(f, "gravity0xB4FBF271143F4FBf7B91A5ded31805e42b2208d6"),
-        }
+        write!(f, "{}", self.0)
+    }
+}
+
+impl AsRef<str> for Denom {
+    fn as_ref(&self) -> &str {
+        self.as_str()
     }
 }
 
@@ -82,18 +227,7 @@ impl TryFrom<String> for Denom {
     type Error = eyre::Report;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        match value.as_str() {
-            "gravity0x6B175474E89094C44Da98b954EedeAC495271d0F" => Ok(Denom::DAI),
-            "gravity0x853d955aCEf822Db058eb8505911ED77F175b99e" => Ok(Denom::FRAX),
-            "usomm" => Ok(Denom::USOMM),
-            "gravity0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48" => Ok(Denom::USDC),
-            "gravity0xdAC17F958D2ee523a2206206994597C13D831ec7" => Ok(Denom::USDT),
-            "gravity0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599" => Ok(Denom::WBTC),
-            "gravity0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2" => Ok(Denom::WETH),
-            "gravity0xd35CCeEAD182dcee0F148EbaC9447DA2c4D449c4" => Ok(Denom::GoerliUSDC),
-            "gravity0xB4FBF271143F4FBf7B91A5ded31805e42b2208d6" => Ok(Denom::GoerliWETH),
-            _ => Err(eyre::eyre!("invalid denom")),
-        }
+        Denom::new(value)
     }
 }
 
@@ -107,7 +241,7 @@ impl TryFrom<&String> for Denom {
 
 impl From<Denom> for String {
     fn from(denom: Denom) -> String {
-        denom.to_string()
+        denom.0.into_owned()
     }
 }
 
@@ -118,3 +252,91 @@ impl FromStr for Denom {
         Denom::try_from(s.to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_denom() {
+        assert_eq!(Denom::USDC, Denom::from_str(Denom::USDC.as_str()).unwrap());
+        assert_eq!("usomm", Denom::USOMM.to_string());
+        assert!(Denom::from_str("gravity0x1111111111111111111111111111111111111111").is_ok());
+        assert!(Denom::from_str("").is_err());
+        assert!(Denom::from_str("0xabc").is_err());
+        assert!(Denom::from_str("bad denom").is_err());
+    }
+
+    #[test]
+    fn test_gravity_denom() {
+        let address: Erc20Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+            .parse()
+            .unwrap();
+
+        assert_eq!(Denom::USDC, Denom::from_erc20(&address));
+        assert_eq!(
+            Denom::USDC,
+            Denom::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap()
+        );
+        assert_eq!(
+            Denom::USDC,
+            Denom::from_str("gravity0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap()
+        );
+        assert_eq!(Some(address), Denom::USDC.erc20_address());
+        assert_eq!(Some(EthereumChain::Mainnet), Denom::USDC.ethereum_chain());
+        assert_eq!(
+            Some(EthereumChain::Goerli),
+            Denom::GOERLI_WETH.ethereum_chain()
+        );
+        assert!(!Denom::USOMM.is_gravity());
+        assert_eq!(None, Denom::USOMM.ethereum_chain());
+        assert!(Denom::from_str("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_parse_ibc_denom() {
+        let denom =
+            Denom::from_str("ibc/27394fb092d2eccd56123c74f36e4c1f926001ceada9ca97ea622b25f41e5eb2")
+                .unwrap();
+
+        assert!(denom.is_ibc());
+        assert_eq!(
+            Some("27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"),
+            denom.ibc_hash()
+        );
+        assert!(!Denom::USDC.is_ibc());
+        assert!(Denom::from_str("ibc/1234").is_err());
+    }
+
+    #[test]
+    fn test_denom_trace() {
+        let trace = DenomTrace::from_str("transfer/channel-0/uatom").unwrap();
+
+        assert_eq!("transfer/channel-0", trace.path);
+        assert_eq!("uatom", trace.base_denom);
+        assert_eq!(vec![("transfer", "channel-0")], trace.hops());
+        assert_eq!(
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+            trace.ibc_denom().unwrap().as_str()
+        );
+
+        let trace =
+            DenomTrace::from_str("transfer/channel-1/transfer/channel-2/gamm/pool/1").unwrap();
+        assert_eq!("transfer/channel-1/transfer/channel-2", trace.path);
+        assert_eq!("gamm/pool/1", trace.base_denom);
+
+        let trace = DenomTrace::from_str("usomm").unwrap();
+        assert_eq!(Denom::USOMM, trace.ibc_denom().unwrap());
+    }
+
+    #[test]
+    fn test_denom_serde() {
+        let json = serde_json::to_string(&Denom::WETH).unwrap();
+        assert_eq!(
+            "\"gravity0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2\"",
+            json
+        );
+        assert_eq!(Denom::WETH, serde_json::from_str::<Denom>(&json).unwrap());
+        assert!(serde_json::from_str::<Denom>("\"\"").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Typed ERC20 contract addresses and the Ethereum chains gravity denoms are bridged from.
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// A 20 byte ERC20 contract address. Parsing accepts any casing; display uses the EIP-55
+/// checksum encoding, which is also how gravity denoms spell addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Erc20Address([u8; 20]);
+
+impl Erc20Address {
+    pub fn new(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+
+    /// Gets the raw address bytes
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// The EIP-55 mixed case checksum encoding, including the 0x prefix
+    pub fn to_checksum(&self) -> String {
+        let lower = hex::encode(self.0);
+        let hash = Keccak256::digest(lower.as_bytes());
+        let checksummed: String = lower
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+
+        format!("0x{checksummed}")
+    }
+}
+
+impl FromStr for Erc20Address {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex_str = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .ok_or_else(|| eyre::eyre!("invalid ERC20 address {s:?}: missing 0x prefix"))?;
+        if hex_str.len() != 40 {
+            return Err(eyre::eyre!(
+                "invalid ERC20 address {s:?}: expected 40 hex characters"
+            ));
+        }
+
+        let mut bytes = [0u8; 20];
+        hex::decode_to_slice(hex_str, &mut bytes)
+            .map_err(|e| eyre::eyre!("invalid ERC20 address {s:?}: {e}"))?;
+
+        Ok(Self(bytes))
+    }
+}
+
+impl TryFrom<String> for Erc20Address {
+    type Error = eyre::Report;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Erc20Address> for String {
+    fn from(address: Erc20Address) -> String {
+        address.to_checksum()
+    }
+}
+
+impl Display for Erc20Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_checksum())
+    }
+}
+
+/// The Ethereum network a gravity denom's ERC20 contract lives on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EthereumChain {
+    Mainnet,
+    Goerli,
+}
+
+impl EthereumChain {
+    /// The EIP-155 chain ID
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            EthereumChain::Mainnet => 1,
+            EthereumChain::Goerli => 5,
+        }
+    }
+}
+
+impl Display for EthereumChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EthereumChain::Mainnet => write!(f, "mainnet"),
+            EthereumChain::Goerli => write!(f, "goerli"),
+        }
+    }
+}
+
+/// The mapping between a gravity bridged denom and its ERC20 contract, as reported by the gravity
+/// module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Erc20Mapping {
+    pub denom: crate::denom::Denom,
+    pub erc20: Erc20Address,
+    /// Whether the token originated on Sommelier and was bridged to Ethereum, rather than the
+    /// other way around
+    pub cosmos_originated: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum() {
+        let address: Erc20Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            address.to_string()
+        );
+        assert_eq!(
+            address,
+            "0xA0B86991C6218B36C1D19D4A2E9EB0CE3606EB48"
+                .parse()
+                .unwrap()
+        );
+        assert!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+            .parse::<Erc20Address>()
+            .is_err());
+        assert!("0xa0b86991".parse::<Erc20Address>().is_err());
+        assert!("0xz0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+            .parse::<Erc20Address>()
+            .is_err());
+    }
+}
@@ -0,0 +1,72 @@
+//! Chain messages and bids shared by the unit tests
+use crate::{
+    amount::TokenAmount,
+    auction::{Auction, Bid as ProtoBid, TokenPrice},
+    bid::Bid,
+    cosmos_sdk_proto::cosmos::base::v1beta1::Coin,
+    denom::Denom,
+};
+
+pub(crate) fn coin(amount: u128, denom: &Denom) -> Option<Coin> {
+    Some(Coin {
+        amount: amount.to_string(),
+        denom: denom.to_string(),
+    })
+}
+
+/// Auction 7, selling `starting` base units of USDC. It started at 2 usomm per base unit and is
+/// down to 1.5, with `remaining` units left.
+pub(crate) fn auction(starting: u128, remaining: u128) -> Auction {
+    Auction {
+        id: 7,
+        starting_tokens_for_sale: coin(starting, &Denom::USDC),
+        remaining_tokens_for_sale: coin(remaining, &Denom::USDC),
+        start_block: 100,
+        initial_price_decrease_rate: "50000000000000000".to_string(),
+        current_price_decrease_rate: "50000000000000000".to_string(),
+        price_decrease_block_interval: 10,
+        initial_unit_price_in_usomm: "2000000000000000000".to_string(),
+        current_unit_price_in_usomm: "1500000000000000000".to_string(),
+        ..Default::default()
+    }
+}
+
+/// A bid on auction 7 for USDC
+pub(crate) fn bid(maximum_usomm_in: u128, minimum_tokens_out: u128) -> Bid {
+    Bid {
+        auction_id: 7,
+        fee_token: Denom::USDC,
+        maximum_usomm_in: TokenAmount::usomm(maximum_usomm_in),
+        minimum_tokens_out: TokenAmount::new(Denom::USDC, minimum_tokens_out),
+    }
+}
+
+/// Bid 1 as the chain records `bid` from somm1abc, which got `fulfilled` base units for `paid`
+/// usomm at `unit_price` usomm each
+pub(crate) fn recorded_bid(bid: &Bid, fulfilled: u128, unit_price: &str, paid: u128) -> ProtoBid {
+    ProtoBid {
+        id: 1,
+        auction_id: bid.auction_id,
+        bidder: "somm1abc".to_string(),
+        max_bid_in_usomm: coin(bid.maximum_usomm_in.raw, &Denom::USOMM),
+        sale_token_minimum_amount: coin(bid.minimum_tokens_out.raw, &bid.fee_token),
+        total_fulfilled_sale_tokens: coin(fulfilled, &bid.fee_token),
+        sale_token_unit_price_in_usomm: unit_price.to_string(),
+        total_usomm_paid: coin(paid, &Denom::USOMM),
+        block_height: 150,
+    }
+}
+
+pub(crate) fn token_price(
+    denom: &Denom,
+    exponent: u64,
+    usd_price: &str,
+    last_updated_block: u64,
+) -> TokenPrice {
+    TokenPrice {
+        denom: denom.to_string(),
+        exponent,
+        usd_price: usd_price.to_string(),
+        last_updated_block,
+    }
+}
@@ -1,6 +1,7 @@
 //! A library for participating in fee auctions on the [Sommelier blockchain](https://sommelier-finance.gitbook.io/sommelier-documentation/protocol/fee-auctions).
 //!
-//! This crate version is compatible with Sommelier v7
+//! This crate version is compatible with Sommelier v7. [`client::Client`] checks the version a node
+//! reports when it is constructed, see [`version::VersionCheck`].
 
 pub use ocular::account::AccountInfo;
 
@@ -9,11 +10,29 @@ pub use sommelier_auction_proto::auction;
 /// Re-export of the Sommelier `cellarfees` module proto bindings
 pub use sommelier_auction_proto::cellarfees;
 pub use sommelier_auction_proto::cosmos_sdk_proto;
+/// Re-export of the gravity module's denom/ERC20 query bindings
+pub use sommelier_auction_proto::gravity;
+/// Re-export of the IBC `transfer` module query bindings
+pub use sommelier_auction_proto::ibc_transfer;
 
+pub mod amount;
 pub mod bid;
 pub mod client;
+pub mod dec;
 pub mod denom;
+pub mod erc20;
+#[cfg(test)]
+mod fixtures;
+pub mod metadata;
 pub mod parameters;
+pub mod price_curve;
+pub mod registry;
+pub mod schedule;
+pub mod signer;
+pub mod simulation;
+pub mod validation;
+pub mod version;
+pub mod view;
 
 pub type BidResult = crate::auction::Bid;
 
@@ -0,0 +1,160 @@
+//! On-chain denom metadata and checks of locally registered decimals against it.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auction::TokenPrice,
+    cosmos_sdk_proto::cosmos::bank::v1beta1::{DenomUnit as ProtoDenomUnit, Metadata},
+    denom::Denom,
+    registry::DenomInfo,
+};
+
+/// A unit of a denom as registered in the bank module
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenomUnit {
+    pub denom: String,
+    /// 10^exponent of this unit equals one base unit
+    pub exponent: u32,
+    pub aliases: Vec<String>,
+}
+
+/// Denom metadata from the bank module
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenomMetadata {
+    pub description: String,
+    pub denom_units: Vec<DenomUnit>,
+    /// The base (smallest) unit, i.e. "usomm"
+    pub base: String,
+    /// The unit shown to users, i.e. "somm"
+    pub display: String,
+}
+
+impl DenomMetadata {
+    /// The exponent of the display unit, which is the number of decimals of one whole token.
+    /// Falls back to the largest exponent if the display unit isn't listed.
+    pub fn decimals(&self) -> Option<u32> {
+        self.denom_units
+            .iter()
+            .find(|u| u.denom == self.display)
+            .or_else(|| self.denom_units.iter().max_by_key(|u| u.exponent))
+            .map(|u| u.exponent)
+    }
+}
+
+impl From<Metadata> for DenomMetadata {
+    fn from(metadata: Metadata) -> Self {
+        Self {
+            description: metadata.description,
+            denom_units: metadata
+                .denom_units
+                .into_iter()
+                .map(|u: ProtoDenomUnit| DenomUnit {
+                    denom: u.denom,
+                    exponent: u.exponent,
+                    aliases: u.aliases,
+                })
+                .collect(),
+            base: metadata.base,
+            display: metadata.display,
+        }
+    }
+}
+
+/// The decimals of a denom according to each source that reports them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecimalsCheck {
+    pub denom: Denom,
+    /// Decimals from the local registry
+    pub registry: u8,
+    /// Decimals from bank module metadata, if the chain has metadata for the denom
+    pub bank_metadata: Option<u32>,
+    /// The exponent of the auction module's token price, if it has a price for the denom
+    pub token_price: Option<u64>,
+}
+
+impl DecimalsCheck {
+    /// Compare a registry entry with the chain's metadata and token price for the same denom
+    pub fn new(
+        info: &DenomInfo,
+        metadata: Option<&DenomMetadata>,
+        token_price: Option<&TokenPrice>,
+    ) -> Self {
+        Self {
+            denom: info.denom.clone(),
+            registry: info.decimals,
+            bank_metadata: metadata.and_then(|m| m.decimals()),
+            token_price: token_price.map(|p| p.exponent),
+        }
+    }
+
+    /// Whether every source that reports decimals agrees with the registry
+    pub fn is_consistent(&self) -> bool {
+        let local = self.registry as u64;
+
+        self.bank_metadata.map_or(true, |d| d as u64 == local)
+            && self.token_price.map_or(true, |d| d == local)
+    }
+
+    /// Whether any chain source reported decimals at all
+    pub fn is_verified(&self) -> bool {
+        self.bank_metadata.is_some() || self.token_price.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn weth_info(decimals: u8) -> DenomInfo {
+        DenomInfo {
+            denom: Denom::WETH,
+            symbol: "WETH".to_string(),
+            decimals,
+            erc20_address: None,
+            price_source_id: None,
+            ibc_trace: None,
+        }
+    }
+
+    fn token_price(exponent: u64) -> TokenPrice {
+        fixtures::token_price(&Denom::WETH, exponent, "2000.0", 1)
+    }
+
+    #[test]
+    fn test_metadata_decimals() {
+        let metadata = DenomMetadata {
+            denom_units: vec![
+                DenomUnit {
+                    denom: "usomm".to_string(),
+                    exponent: 0,
+                    aliases: vec![],
+                },
+                DenomUnit {
+                    denom: "somm".to_string(),
+                    exponent: 6,
+                    aliases: vec![],
+                },
+            ],
+            base: "usomm".to_string(),
+            display: "somm".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(Some(6), metadata.decimals());
+        assert_eq!(None, DenomMetadata::default().decimals());
+    }
+
+    #[test]
+    fn test_decimals_check() {
+        let check = DecimalsCheck::new(&weth_info(18), None, Some(&token_price(18)));
+        assert!(check.is_consistent());
+        assert!(check.is_verified());
+
+        let check = DecimalsCheck::new(&weth_info(6), None, Some(&token_price(18)));
+        assert!(!check.is_consistent());
+
+        let check = DecimalsCheck::new(&weth_info(18), None, None);
+        assert!(check.is_consistent());
+        assert!(!check.is_verified());
+    }
+}
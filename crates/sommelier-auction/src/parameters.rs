@@ -1,7 +1,14 @@
+use eyre::{eyre, Result};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-/// The parameters for an auction. 
+use crate::{
+    auction::Params as AuctionParams,
+    cellarfees::Params as CellarfeesParams,
+    dec::{parse_field, Dec},
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+/// The parameters of the `auction` and `cellarfees` modules that govern auctions
 pub struct AuctionParameters {
     /// If there are qualifying fees, auctions occur when block_height % auction_interval == 0
     pub auction_interval: u64,
@@ -10,11 +17,269 @@ pub struct AuctionParameters {
     /// Frequency of auction uSOMM price decrease in blocks
     pub price_decrease_block_interval: u64,
     /// The initial price decrease rate for auction uSOMM price
-    pub initial_price_decrease_rate: f64,
+    pub initial_price_decrease_rate: Dec,
+    /// Number of blocks over which cellarfees distributes SOMM auction proceeds as staking rewards
+    pub reward_emission_period: u64,
     /// Acceleration factor for the price decrease rate
-    pub auction_price_decrease_acceleration_rate: f64,
+    pub auction_price_decrease_acceleration_rate: Dec,
     /// The minimum total uSOMM required for a bid to be valid
     pub minimum_bid_in_usomm: u64,
     /// The minimum total usd value of fee tokens requested in a bid
-    pub minimum_sale_tokens_usd_value: u64,
+    pub minimum_sale_tokens_usd_value: Dec,
+    /// The most blocks since a token price was last updated for it to be used in an auction
+    pub price_max_block_age: u64,
+    /// The most blocks an auction record is kept after it ends
+    pub auction_max_block_age: u64,
+    /// The lowest block height at which auctions can start
+    pub minimum_auction_height: u64,
+}
+
+/// A parameter whose value differs between two [`AuctionParameters`] snapshots
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ParameterChange {
+    pub name: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl std::fmt::Display for ParameterChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.name, self.old, self.new)
+    }
+}
+
+impl AuctionParameters {
+    /// Combines the parameters of both modules, failing if any decimal parameter is malformed
+    pub fn from_params(auction: AuctionParams, cellarfees: CellarfeesParams) -> Result<Self> {
+        let parameters = Self {
+            auction_interval: cellarfees.auction_interval,
+            fee_accrual_auction_threshold: cellarfees.fee_accrual_auction_threshold,
+            price_decrease_block_interval: cellarfees.price_decrease_block_interval,
+            initial_price_decrease_rate: parse_field(
+                &cellarfees.initial_price_decrease_rate,
+                "initial_price_decrease_rate",
+            )?,
+            reward_emission_period: cellarfees.reward_emission_period,
+            auction_price_decrease_acceleration_rate: parse_field(
+                &auction.auction_price_decrease_acceleration_rate,
+                "auction_price_decrease_acceleration_rate",
+            )?,
+            minimum_bid_in_usomm: auction.minimum_bid_in_usomm,
+            minimum_sale_tokens_usd_value: parse_field(
+                &auction.minimum_sale_tokens_usd_value,
+                "minimum_sale_tokens_usd_value",
+            )?,
+            price_max_block_age: auction.price_max_block_age,
+            auction_max_block_age: auction.auction_max_block_age,
+            minimum_auction_height: auction.minimum_auction_height,
+        };
+
+        Ok(parameters)
+    }
+
+    /// Checks the parameters are within the bounds the modules enforce
+    pub fn validate(&self) -> Result<()> {
+        let positive = [
+            ("auction_interval", self.auction_interval),
+            (
+                "price_decrease_block_interval",
+                self.price_decrease_block_interval,
+            ),
+            ("reward_emission_period", self.reward_emission_period),
+            ("price_max_block_age", self.price_max_block_age),
+        ];
+        for (name, value) in positive {
+            if value == 0 {
+                return Err(eyre!("{name} must be positive"));
+            }
+        }
+
+        let rates = [
+            (
+                "initial_price_decrease_rate",
+                self.initial_price_decrease_rate,
+            ),
+            (
+                "auction_price_decrease_acceleration_rate",
+                self.auction_price_decrease_acceleration_rate,
+            ),
+        ];
+        for (name, value) in rates {
+            if value.is_negative() || value > Dec::ONE {
+                return Err(eyre!("{name} must be between 0 and 1, got {value}"));
+            }
+        }
+        if self.initial_price_decrease_rate.is_zero() {
+            return Err(eyre!("initial_price_decrease_rate must be positive"));
+        }
+        if self.minimum_sale_tokens_usd_value.is_negative() {
+            return Err(eyre!(
+                "minimum_sale_tokens_usd_value can't be negative, got {}",
+                self.minimum_sale_tokens_usd_value
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Lists the parameters that changed from `self` to `new`
+    pub fn diff(&self, new: &AuctionParameters) -> Vec<ParameterChange> {
+        let fields = [
+            (
+                "auction_interval",
+                self.auction_interval.to_string(),
+                new.auction_interval.to_string(),
+            ),
+            (
+                "fee_accrual_auction_threshold",
+                self.fee_accrual_auction_threshold.to_string(),
+                new.fee_accrual_auction_threshold.to_string(),
+            ),
+            (
+                "price_decrease_block_interval",
+                self.price_decrease_block_interval.to_string(),
+                new.price_decrease_block_interval.to_string(),
+            ),
+            (
+                "initial_price_decrease_rate",
+                self.initial_price_decrease_rate.to_string(),
+                new.initial_price_decrease_rate.to_string(),
+            ),
+            (
+                "reward_emission_period",
+                self.reward_emission_period.to_string(),
+                new.reward_emission_period.to_string(),
+            ),
+            (
+                "auction_price_decrease_acceleration_rate",
+                self.auction_price_decrease_acceleration_rate.to_string(),
+                new.auction_price_decrease_acceleration_rate.to_string(),
+            ),
+            (
+                "minimum_bid_in_usomm",
+                self.minimum_bid_in_usomm.to_string(),
+                new.minimum_bid_in_usomm.to_string(),
+            ),
+            (
+                "minimum_sale_tokens_usd_value",
+                self.minimum_sale_tokens_usd_value.to_string(),
+                new.minimum_sale_tokens_usd_value.to_string(),
+            ),
+            (
+                "price_max_block_age",
+                self.price_max_block_age.to_string(),
+                new.price_max_block_age.to_string(),
+            ),
+            (
+                "auction_max_block_age",
+                self.auction_max_block_age.to_string(),
+                new.auction_max_block_age.to_string(),
+            ),
+            (
+                "minimum_auction_height",
+                self.minimum_auction_height.to_string(),
+                new.minimum_auction_height.to_string(),
+            ),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, old, new)| old != new)
+            .map(|(name, old, new)| ParameterChange {
+                name: name.to_string(),
+                old,
+                new,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters() -> AuctionParameters {
+        AuctionParameters::from_params(
+            AuctionParams {
+                price_max_block_age: 1000,
+                minimum_bid_in_usomm: 1_000_000,
+                minimum_sale_tokens_usd_value: "1000000000000000000".to_string(),
+                auction_max_block_age: 100_000,
+                auction_price_decrease_acceleration_rate: "1000000000000000".to_string(),
+                minimum_auction_height: 0,
+            },
+            CellarfeesParams {
+                fee_accrual_auction_threshold: 2,
+                reward_emission_period: 28_800,
+                initial_price_decrease_rate: "64800000000000".to_string(),
+                price_decrease_block_interval: 10,
+                auction_interval: 15_000,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_params() {
+        let parameters = parameters();
+
+        assert_eq!(Dec::ONE, parameters.minimum_sale_tokens_usd_value);
+        assert_eq!(
+            "0.001".parse::<Dec>().unwrap(),
+            parameters.auction_price_decrease_acceleration_rate
+        );
+        assert!(parameters.validate().is_ok());
+
+        assert!(AuctionParameters::from_params(
+            AuctionParams {
+                minimum_sale_tokens_usd_value: "one dollar".to_string(),
+                ..Default::default()
+            },
+            CellarfeesParams::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let invalid = AuctionParameters {
+            auction_interval: 0,
+            ..parameters()
+        };
+        assert!(invalid.validate().is_err());
+
+        let invalid = AuctionParameters {
+            initial_price_decrease_rate: "1.5".parse().unwrap(),
+            ..parameters()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = parameters();
+        assert!(old.diff(&old).is_empty());
+
+        let new = AuctionParameters {
+            minimum_bid_in_usomm: 2_000_000,
+            auction_interval: 10_000,
+            ..old.clone()
+        };
+
+        assert_eq!(
+            vec![
+                ParameterChange {
+                    name: "auction_interval".to_string(),
+                    old: "15000".to_string(),
+                    new: "10000".to_string(),
+                },
+                ParameterChange {
+                    name: "minimum_bid_in_usomm".to_string(),
+                    old: "1000000".to_string(),
+                    new: "2000000".to_string(),
+                },
+            ],
+            old.diff(&new)
+        );
+    }
 }
@@ -0,0 +1,290 @@
+//! A model of how an auction's unit price decays, for predicting future prices.
+//!
+//! Every `price_decrease_block_interval` blocks after an auction starts, the auction module
+//! accelerates the price decrease rate and then lowers the unit price by a fraction of the
+//! initial price:
+//!
+//! ```text
+//! rate  = rate + rate * auction_price_decrease_acceleration_rate
+//! price = price - initial_unit_price * rate
+//! ```
+//!
+//! The auction ends once the price is no longer positive. All math uses [`Dec`], which rounds the
+//! same way the chain does, so predictions match on-chain prices exactly.
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{dec::Dec, parameters::AuctionParameters, view::AuctionView};
+
+/// The most decrease steps simulated before giving up on a prediction
+const MAX_STEPS: u64 = 1_000_000;
+
+/// The projected unit price of an auction over time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceCurve {
+    pub auction_id: u32,
+    pub start_block: u64,
+    pub block_interval: u64,
+    pub initial_unit_price: Dec,
+    pub acceleration_rate: Dec,
+    /// Number of decreases already applied to the auction's current state
+    pub steps: u64,
+    /// The auction's current unit price
+    pub unit_price: Dec,
+    /// The auction's current price decrease rate
+    pub decrease_rate: Dec,
+}
+
+impl PriceCurve {
+    /// Builds the curve from an auction's current state. The number of decreases already applied
+    /// is recovered by replaying the rate from its initial value until it matches the current one,
+    /// or, when the rate doesn't accelerate, from how far the price has fallen. No block height is
+    /// needed.
+    pub fn new(auction: &AuctionView, parameters: &AuctionParameters) -> Result<Self> {
+        if auction.price_decrease_block_interval == 0 {
+            return Err(eyre!(
+                "auction {} has a price decrease interval of zero",
+                auction.id
+            ));
+        }
+
+        let acceleration_rate = parameters.auction_price_decrease_acceleration_rate;
+        let mut rate = auction.initial_price_decrease_rate;
+        let mut steps = 0;
+        while rate != auction.current_price_decrease_rate {
+            let next = next_rate(rate, acceleration_rate)
+                .ok_or_else(|| eyre!("overflow replaying auction {} decrease rate", auction.id))?;
+            if steps >= MAX_STEPS || next == rate || rate > auction.current_price_decrease_rate {
+                return Err(eyre!(
+                    "auction {} decrease rate {} is not reachable from initial rate {} with acceleration {}",
+                    auction.id,
+                    auction.current_price_decrease_rate,
+                    auction.initial_price_decrease_rate,
+                    acceleration_rate
+                ));
+            }
+
+            rate = next;
+            steps += 1;
+        }
+
+        // a constant rate says nothing about how many decreases have happened, but each one takes
+        // the same amount off the price
+        if next_rate(rate, acceleration_rate) == Some(rate) {
+            steps = constant_rate_steps(auction)?;
+        }
+
+        Ok(Self {
+            auction_id: auction.id,
+            start_block: auction.start_block,
+            block_interval: auction.price_decrease_block_interval,
+            initial_unit_price: auction.initial_unit_price_in_usomm,
+            acceleration_rate,
+            steps,
+            unit_price: auction.current_unit_price_in_usomm,
+            decrease_rate: auction.current_price_decrease_rate,
+        })
+    }
+
+    /// The block at which the `step`th price decrease happens
+    pub fn block_of_step(&self, step: u64) -> u64 {
+        self.start_block + step * self.block_interval
+    }
+
+    /// The number of price decreases that will have happened by `height`
+    pub fn steps_at(&self, height: u64) -> u64 {
+        height.saturating_sub(self.start_block) / self.block_interval
+    }
+
+    /// The first block after `height` at which the price decreases
+    pub fn next_decrease_block(&self, height: u64) -> u64 {
+        self.block_of_step(self.steps_at(height) + 1)
+    }
+
+    /// The predicted unit price at `height`. Heights before the current state return the current
+    /// price, and zero is returned once the auction would have ended.
+    pub fn price_at(&self, height: u64) -> Dec {
+        let target = self.steps_at(height);
+        let mut price = self.unit_price;
+        for (_, step_price) in self.steps().take_while(|(step, _)| *step <= target) {
+            price = step_price;
+        }
+
+        if price.is_negative() {
+            return Dec::ZERO;
+        }
+
+        price
+    }
+
+    /// The first block at which the unit price is at or below `target`, or `None` if the auction
+    /// would end first
+    pub fn block_for_price(&self, target: Dec) -> Option<u64> {
+        if self.unit_price <= target {
+            return Some(self.block_of_step(self.steps));
+        }
+        if !target.is_positive() {
+            return None;
+        }
+
+        self.steps()
+            .find(|(_, price)| *price <= target)
+            .filter(|(_, price)| price.is_positive())
+            .map(|(step, _)| self.block_of_step(step))
+    }
+
+    /// The block at which the price reaches zero and the auction ends, if no bids fill it first
+    pub fn end_block(&self) -> Option<u64> {
+        self.steps()
+            .find(|(_, price)| !price.is_positive())
+            .map(|(step, _)| self.block_of_step(step))
+    }
+
+    /// Iterates over each future step and the unit price after it, ending with the step that
+    /// takes the price to zero or below
+    fn steps(&self) -> impl Iterator<Item = (u64, Dec)> + '_ {
+        let mut state = Some((self.steps, self.unit_price, self.decrease_rate));
+        std::iter::from_fn(move || {
+            let (step, price, rate) = state?;
+            if !price.is_positive() || step - self.steps >= MAX_STEPS {
+                state = None;
+                return None;
+            }
+
+            let rate = next_rate(rate, self.acceleration_rate)?;
+            let price = price.checked_sub(self.initial_unit_price.checked_mul(rate)?)?;
+            state = Some((step + 1, price, rate));
+
+            Some((step + 1, price))
+        })
+    }
+}
+
+// Number of decreases of `initial_unit_price * rate` that take the initial price to the current one
+fn constant_rate_steps(auction: &AuctionView) -> Result<u64> {
+    let decrease = auction
+        .initial_unit_price_in_usomm
+        .checked_mul(auction.current_price_decrease_rate)
+        .filter(Dec::is_positive)
+        .ok_or_else(|| eyre!("auction {} has no price decrease", auction.id))?;
+    let fallen = auction
+        .initial_unit_price_in_usomm
+        .checked_sub(auction.current_unit_price_in_usomm)
+        .filter(|d| !d.is_negative())
+        .ok_or_else(|| {
+            eyre!(
+                "auction {} price {} is above its initial price {}",
+                auction.id,
+                auction.current_unit_price_in_usomm,
+                auction.initial_unit_price_in_usomm
+            )
+        })?;
+
+    let steps = fallen.atomics() / decrease.atomics();
+    if fallen.atomics() % decrease.atomics() != 0 {
+        return Err(eyre!(
+            "auction {} price {} is not a whole number of decreases of {} below its initial price {}",
+            auction.id,
+            auction.current_unit_price_in_usomm,
+            decrease,
+            auction.initial_unit_price_in_usomm
+        ));
+    }
+
+    u64::try_from(steps).map_err(|_| eyre!("auction {} has too many decreases", auction.id))
+}
+
+fn next_rate(rate: Dec, acceleration_rate: Dec) -> Option<Dec> {
+    rate.checked_add(rate.checked_mul(acceleration_rate)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{amount::TokenAmount, denom::Denom, view::AuctionStatus};
+
+    fn dec(s: &str) -> Dec {
+        s.parse().unwrap()
+    }
+
+    fn parameters(acceleration: &str) -> AuctionParameters {
+        AuctionParameters {
+            auction_price_decrease_acceleration_rate: dec(acceleration),
+            ..Default::default()
+        }
+    }
+
+    fn auction(current_price: &str, current_rate: &str) -> AuctionView {
+        AuctionView {
+            id: 1,
+            status: AuctionStatus::Active,
+            starting_tokens_for_sale: TokenAmount::new(Denom::USDC, 1000),
+            remaining_tokens_for_sale: TokenAmount::new(Denom::USDC, 1000),
+            start_block: 100,
+            end_block: None,
+            initial_price_decrease_rate: dec("0.1"),
+            current_price_decrease_rate: dec(current_rate),
+            price_decrease_block_interval: 10,
+            initial_unit_price_in_usomm: dec("10"),
+            current_unit_price_in_usomm: dec(current_price),
+            funding_module_account: String::new(),
+            proceeds_module_account: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_constant_rate() {
+        let curve = PriceCurve::new(&auction("10", "0.1"), &parameters("0")).unwrap();
+
+        assert_eq!(0, curve.steps);
+        assert_eq!(dec("10"), curve.price_at(105));
+        assert_eq!(dec("9"), curve.price_at(110));
+        assert_eq!(dec("5"), curve.price_at(159));
+        assert_eq!(Dec::ZERO, curve.price_at(1000));
+        assert_eq!(Some(150), curve.block_for_price(dec("5")));
+        assert_eq!(Some(160), curve.block_for_price(dec("4.5")));
+        assert_eq!(Some(200), curve.end_block());
+        assert_eq!(120, curve.next_decrease_block(110));
+    }
+
+    #[test]
+    fn test_constant_rate_after_decreases() {
+        // three steps in: 10 - 3 * 10 * 0.1
+        let curve = PriceCurve::new(&auction("7", "0.1"), &parameters("0")).unwrap();
+
+        assert_eq!(3, curve.steps);
+        assert_eq!(dec("7"), curve.price_at(130));
+        assert_eq!(dec("7"), curve.price_at(139));
+        assert_eq!(dec("6"), curve.price_at(140));
+        assert_eq!(dec("5"), curve.price_at(159));
+        assert_eq!(Some(130), curve.block_for_price(dec("7")));
+        assert_eq!(Some(150), curve.block_for_price(dec("5")));
+        assert_eq!(Some(200), curve.end_block());
+        assert_eq!(Dec::ZERO, curve.price_at(1000));
+
+        // between whole decreases, or above the initial price
+        assert!(PriceCurve::new(&auction("7.5", "0.1"), &parameters("0")).is_err());
+        assert!(PriceCurve::new(&auction("11", "0.1"), &parameters("0")).is_err());
+    }
+
+    #[test]
+    fn test_accelerating_rate() {
+        // one step in: rate 0.1 * 1.5 = 0.15, price 10 - 1.5 = 8.5
+        let curve = PriceCurve::new(&auction("8.5", "0.15"), &parameters("0.5")).unwrap();
+
+        assert_eq!(1, curve.steps);
+        assert_eq!(dec("8.5"), curve.price_at(110));
+        // rate 0.225, price 8.5 - 2.25
+        assert_eq!(dec("6.25"), curve.price_at(120));
+        // rate 0.3375, price 6.25 - 3.375
+        assert_eq!(dec("2.875"), curve.price_at(130));
+        assert_eq!(Some(130), curve.block_for_price(dec("3")));
+        assert_eq!(Some(140), curve.end_block());
+        assert_eq!(None, curve.block_for_price(dec("1")));
+    }
+
+    #[test]
+    fn test_unreachable_rate() {
+        assert!(PriceCurve::new(&auction("8.5", "0.17"), &parameters("0.5")).is_err());
+    }
+}
@@ -0,0 +1,450 @@
+//! A registry of denom metadata. Replaces hardcoded per-denom decimals and symbols so that
+//! auctions for any fee token can be handled once the token is registered.
+use std::{collections::HashMap, path::Path};
+
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    denom::{Denom, DenomTrace},
+    erc20::Erc20Address,
+};
+
+/// Metadata for a single denom
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenomInfo {
+    /// The on-chain denom
+    pub denom: Denom,
+    /// Human readable symbol, i.e. "WETH"
+    pub symbol: String,
+    /// The number of decimals of one whole token
+    pub decimals: u8,
+    /// The ERC20 contract address of the token, if it is bridged from Ethereum
+    #[serde(default)]
+    pub erc20_address: Option<Erc20Address>,
+    /// The ID used to look up the token's USD price, i.e. a CoinGecko ID
+    #[serde(default)]
+    pub price_source_id: Option<String>,
+    /// The origin of an IBC denom
+    #[serde(default)]
+    pub ibc_trace: Option<DenomTrace>,
+}
+
+impl DenomInfo {
+    /// Checks an IBC entry is registered under the denom its trace hashes to. Under any other
+    /// denom, the entry's symbol, decimals and price would be used for a different token.
+    pub fn validate(&self) -> Result<()> {
+        let Some(trace) = &self.ibc_trace else {
+            return Ok(());
+        };
+
+        let expected = trace.ibc_denom()?;
+        if expected != self.denom {
+            return Err(eyre!(
+                "denom {} does not match its ibc_trace {trace}, which is {expected}",
+                self.denom
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// On-disk format of a [`DenomRegistry`]
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    denoms: Vec<DenomInfo>,
+}
+
+/// A collection of [`DenomInfo`] keyed by denom. Can be loaded from TOML or JSON and extended at
+/// runtime.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DenomRegistry {
+    entries: HashMap<Denom, DenomInfo>,
+}
+
+impl DenomRegistry {
+    /// Construct an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a registry containing the denoms this crate knows about out of the box
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        let builtin = [
+            (Denom::USOMM, "USOMM", 6, "sommelier"),
+            (Denom::DAI, "DAI", 18, "dai"),
+            (Denom::FRAX, "FRAX", 18, "frax"),
+            (Denom::USDC, "USDC", 6, "usd-coin"),
+            (Denom::USDT, "USDT", 6, "tether"),
+            (Denom::WBTC, "WBTC", 8, "wrapped-bitcoin"),
+            (Denom::WETH, "WETH", 18, "weth"),
+            // for testing
+            (Denom::GOERLI_USDC, "GoerliUSDC", 6, "usd-coin"),
+            (Denom::GOERLI_WETH, "GoerliWETH", 18, "weth"),
+        ];
+
+        for (denom, symbol, decimals, price_source_id) in builtin {
+            let erc20_address = denom.erc20_address();
+            registry.register(DenomInfo {
+                denom,
+                symbol: symbol.to_string(),
+                decimals,
+                erc20_address,
+                price_source_id: Some(price_source_id.to_string()),
+                ibc_trace: None,
+            });
+        }
+
+        // IBC tokens aren't builtin. The same base denom means a different token depending on the
+        // channel it arrived over, e.g. uusdc from Axelar or from Noble, so each one has to be
+        // registered with its full trace path, see [`DenomInfo::validate`].
+
+        registry
+    }
+
+    /// Construct a registry from `entries`, returning an error if any of them is invalid, see
+    /// [`DenomInfo::validate`]
+    pub fn from_entries(entries: impl IntoIterator<Item = DenomInfo>) -> Result<Self> {
+        entries
+            .into_iter()
+            .map(|info| info.validate().map(|()| info))
+            .collect()
+    }
+
+    /// Parse a registry from a TOML document containing a `[[denoms]]` array
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        let file: RegistryFile = toml::from_str(s)?;
+
+        Self::from_entries(file.denoms)
+    }
+
+    /// Parse a registry from a JSON document of the form `{"denoms": [...]}`
+    pub fn from_json_str(s: &str) -> Result<Self> {
+        let file: RegistryFile = serde_json::from_str(s)?;
+
+        Self::from_entries(file.denoms)
+    }
+
+    /// Load a registry from a `.toml` or `.json` file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml_str(&contents),
+            Some("json") => Self::from_json_str(&contents),
+            _ => Err(eyre!(
+                "unsupported denom registry file {}. expected a .toml or .json extension",
+                path.display()
+            )),
+        }
+    }
+
+    /// Serialize the registry as TOML
+    pub fn to_toml_string(&self) -> Result<String> {
+        Ok(toml::to_string(&self.to_file())?)
+    }
+
+    /// Serialize the registry as JSON
+    pub fn to_json_string(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.to_file())?)
+    }
+
+    fn to_file(&self) -> RegistryFile {
+        let mut denoms: Vec<DenomInfo> = self.entries.values().cloned().collect();
+        denoms.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+        RegistryFile { denoms }
+    }
+
+    /// Add or replace an entry, returning the previous entry for the denom if there was one
+    pub fn register(&mut self, info: DenomInfo) -> Option<DenomInfo> {
+        self.entries.insert(info.denom.clone(), info)
+    }
+
+    /// Checks the entry for an IBC denom against `trace`, the denom's trace on chain, recording
+    /// the trace on entries registered without one. Returns an error if the trace doesn't hash to
+    /// the denom, the denom isn't registered or its entry is for another trace, since a base denom
+    /// alone doesn't say which token it is.
+    pub fn verify_ibc(&mut self, denom: &Denom, trace: DenomTrace) -> Result<&DenomInfo> {
+        if &trace.ibc_denom()? != denom {
+            return Err(eyre!("denom trace {trace} does not match {denom}"));
+        }
+
+        let info = self.get(denom).ok_or_else(|| {
+            eyre!("{denom} ({trace}) is not registered. register it with its ibc_trace")
+        })?;
+        match &info.ibc_trace {
+            Some(registered) if registered.full_path() != trace.full_path() => {
+                return Err(eyre!(
+                    "{denom} is registered for {registered} but is {trace} on chain"
+                ));
+            }
+            Some(_) => {}
+            None => {
+                let info = DenomInfo {
+                    ibc_trace: Some(trace),
+                    ..info.clone()
+                };
+                self.register(info);
+            }
+        }
+
+        self.resolve(denom)
+    }
+
+    /// Add or replace all entries of `other`
+    pub fn extend(&mut self, other: DenomRegistry) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Remove an entry
+    pub fn remove(&mut self, denom: &Denom) -> Option<DenomInfo> {
+        self.entries.remove(denom)
+    }
+
+    /// Gets the entry for a denom
+    pub fn get(&self, denom: &Denom) -> Option<&DenomInfo> {
+        self.entries.get(denom)
+    }
+
+    /// Gets the entry for a denom, returning an error if it isn't registered
+    pub fn resolve(&self, denom: &Denom) -> Result<&DenomInfo> {
+        self.get(denom)
+            .ok_or_else(|| eyre!("denom {denom} is not registered"))
+    }
+
+    /// Gets the first entry with the given symbol, ignoring case
+    pub fn get_by_symbol(&self, symbol: &str) -> Option<&DenomInfo> {
+        self.entries
+            .values()
+            .find(|info| info.symbol.eq_ignore_ascii_case(symbol))
+    }
+
+    /// Gets the entry for a token by its ERC20 contract address
+    pub fn get_by_erc20(&self, address: &Erc20Address) -> Option<&DenomInfo> {
+        self.entries
+            .values()
+            .find(|info| info.erc20_address.as_ref() == Some(address))
+    }
+
+    /// Gets the entry for an IBC token by its full trace path
+    pub fn get_by_trace(&self, trace: &DenomTrace) -> Option<&DenomInfo> {
+        self.entries.values().find(|info| {
+            info.ibc_trace.as_ref().map(DenomTrace::full_path) == Some(trace.full_path())
+        })
+    }
+
+    /// Gets the decimals of a registered denom
+    pub fn decimals(&self, denom: &Denom) -> Option<u8> {
+        self.get(denom).map(|info| info.decimals)
+    }
+
+    /// Gets the symbol of a registered denom
+    pub fn symbol(&self, denom: &Denom) -> Option<&str> {
+        self.get(denom).map(|info| info.symbol.as_str())
+    }
+
+    /// Gets the price source ID of a registered denom
+    pub fn price_source_id(&self, denom: &Denom) -> Option<&str> {
+        self.get(denom)
+            .and_then(|info| info.price_source_id.as_deref())
+    }
+
+    /// Whether the denom is registered
+    pub fn contains(&self, denom: &Denom) -> bool {
+        self.entries.contains_key(denom)
+    }
+
+    /// Iterate over all entries in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &DenomInfo> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl FromIterator<DenomInfo> for DenomRegistry {
+    fn from_iter<T: IntoIterator<Item = DenomInfo>>(iter: T) -> Self {
+        let mut registry = Self::new();
+        iter.into_iter().for_each(|info| {
+            registry.register(info);
+        });
+
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+[[denoms]]
+denom = "gravity0x1111111111111111111111111111111111111111"
+symbol = "NEW"
+decimals = 9
+erc20_address = "0x1111111111111111111111111111111111111111"
+price_source_id = "new-token"
+
+[[denoms]]
+denom = "gravity0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+symbol = "WETH"
+decimals = 18
+"#;
+
+    #[test]
+    fn test_load_toml_and_extend() {
+        let mut registry = DenomRegistry::builtin();
+        let loaded = DenomRegistry::from_toml_str(TOML).unwrap();
+        assert_eq!(2, loaded.len());
+
+        registry.extend(loaded);
+
+        let new_denom = Denom::new("gravity0x1111111111111111111111111111111111111111").unwrap();
+        assert_eq!(Some(9), registry.decimals(&new_denom));
+        assert_eq!(Some("new-token"), registry.price_source_id(&new_denom));
+        assert_eq!(&new_denom, &registry.get_by_symbol("new").unwrap().denom);
+        // loaded entries replace builtin ones
+        assert_eq!(None, registry.price_source_id(&Denom::WETH));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let registry = DenomRegistry::builtin();
+        let json = registry.to_json_string().unwrap();
+
+        assert_eq!(registry, DenomRegistry::from_json_str(&json).unwrap());
+        assert_eq!(
+            registry,
+            DenomRegistry::from_toml_str(&registry.to_toml_string().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_builtin() {
+        let registry = DenomRegistry::builtin();
+
+        assert_eq!(Some(6), registry.decimals(&Denom::USOMM));
+        assert_eq!(Some(8), registry.decimals(&Denom::WBTC));
+        assert_eq!(Some("USDC"), registry.symbol(&Denom::USDC));
+        let usdc_address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Some(usdc_address),
+            registry.get(&Denom::USDC).unwrap().erc20_address
+        );
+        assert_eq!(
+            Denom::USDC,
+            registry.get_by_erc20(&usdc_address).unwrap().denom
+        );
+        assert!(registry.resolve(&Denom::new("unknown").unwrap()).is_err());
+    }
+
+    fn axl_usdc_trace() -> DenomTrace {
+        "transfer/channel-5/uusdc".parse().unwrap()
+    }
+
+    fn axl_usdc() -> DenomInfo {
+        let trace = axl_usdc_trace();
+
+        DenomInfo {
+            denom: trace.ibc_denom().unwrap(),
+            symbol: "axlUSDC".to_string(),
+            decimals: 6,
+            erc20_address: None,
+            price_source_id: Some("axlusdc".to_string()),
+            ibc_trace: Some(trace),
+        }
+    }
+
+    fn ibc_toml(denom: &str) -> String {
+        format!(
+            r#"
+[[denoms]]
+denom = "{denom}"
+symbol = "axlUSDC"
+decimals = 6
+ibc_trace = {{ path = "transfer/channel-5", base_denom = "uusdc" }}
+"#
+        )
+    }
+
+    #[test]
+    fn test_ibc_entries_must_match_their_trace() {
+        let denom = axl_usdc_trace().ibc_denom().unwrap();
+        let registry = DenomRegistry::from_toml_str(&ibc_toml(denom.as_ref())).unwrap();
+        assert_eq!(Some(6), registry.decimals(&denom));
+        assert!(axl_usdc().validate().is_ok());
+
+        // keyed by the base denom or another trace's hash
+        let noble = "transfer/channel-9/uusdc"
+            .parse::<DenomTrace>()
+            .unwrap()
+            .ibc_denom()
+            .unwrap();
+        for wrong in ["uusdc", noble.as_ref()] {
+            let err = DenomRegistry::from_toml_str(&ibc_toml(wrong)).unwrap_err();
+            assert!(err.to_string().contains("does not match"), "{err}");
+        }
+
+        let json = DenomRegistry::from_iter([DenomInfo {
+            denom: noble,
+            ..axl_usdc()
+        }])
+        .to_json_string()
+        .unwrap();
+        assert!(DenomRegistry::from_json_str(&json).is_err());
+    }
+
+    #[test]
+    fn test_verify_ibc() {
+        let mut registry = DenomRegistry::builtin();
+        registry.register(axl_usdc());
+        let trace = axl_usdc_trace();
+        let denom = trace.ibc_denom().unwrap();
+
+        let info = registry.verify_ibc(&denom, trace.clone()).unwrap();
+        assert_eq!("axlUSDC", info.symbol);
+        assert_eq!(Some(trace.clone()), info.ibc_trace);
+
+        // trace doesn't hash to the denom
+        let other: DenomTrace = "transfer/channel-6/uusdc".parse().unwrap();
+        assert!(registry.verify_ibc(&denom, other).is_err());
+
+        // the same base denom over another channel is a different token
+        let noble: DenomTrace = "transfer/channel-9/uusdc".parse().unwrap();
+        let noble_denom = noble.ibc_denom().unwrap();
+        assert!(registry.verify_ibc(&noble_denom, noble).is_err());
+        assert!(!registry.contains(&noble_denom));
+
+        // entries registered without a trace get the one on chain
+        registry.register(DenomInfo {
+            ibc_trace: None,
+            ..axl_usdc()
+        });
+        registry.verify_ibc(&denom, trace.clone()).unwrap();
+        assert_eq!(Some(trace), registry.get(&denom).unwrap().ibc_trace);
+    }
+
+    #[test]
+    fn test_builtin_has_no_ibc_denoms() {
+        let mut registry = DenomRegistry::builtin();
+        assert!(registry.get(&Denom::new("uusdc").unwrap()).is_none());
+        assert!(registry.iter().all(|info| !info.denom.is_ibc()));
+
+        let trace = axl_usdc_trace();
+        let denom = trace.ibc_denom().unwrap();
+        assert!(registry.verify_ibc(&denom, trace).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Predictions of when the cellarfees module will start auctions for accrued fees.
+//!
+//! At every block height that is a multiple of `auction_interval`, cellarfees starts an auction
+//! for each fee denom that has accrued at least `fee_accrual_auction_threshold` times since its
+//! last auction, selling the module account's whole balance of that denom. A denom that already
+//! has an active auction is skipped, as are heights below the auction module's
+//! `minimum_auction_height`.
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{amount::TokenAmount, denom::Denom, parameters::AuctionParameters};
+
+/// Sommelier's typical block time, used when it can't be measured
+pub const DEFAULT_BLOCK_TIME: Duration = Duration::from_secs(6);
+
+/// The auction outlook for one fee denom
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpcomingAuction {
+    pub denom: Denom,
+    /// Fee accruals since the denom's last auction
+    pub accrual_count: u64,
+    /// The cellarfees module account's balance of the denom, which is what would be auctioned
+    pub fee_balance: TokenAmount,
+    /// Whether the denom already has an auction running
+    pub has_active_auction: bool,
+    /// Whether an auction will start for the denom at the next auction height
+    pub qualifies: bool,
+    /// The block the next auction for the denom is expected to start at, if it qualifies
+    pub expected_start_block: Option<u64>,
+    /// When the next auction for the denom is expected to start, if it qualifies
+    pub expected_start_time: Option<SystemTime>,
+}
+
+/// The chain state an auction schedule is predicted from
+#[derive(Debug, Clone)]
+pub struct ScheduleInputs {
+    pub height: u64,
+    pub time: SystemTime,
+    pub block_time: Duration,
+    pub parameters: AuctionParameters,
+    /// Accrual counts by denom
+    pub accruals: Vec<(Denom, u64)>,
+    /// The cellarfees module account's balances
+    pub fee_balances: Vec<TokenAmount>,
+    /// Denoms with an active auction
+    pub active_auction_denoms: Vec<Denom>,
+}
+
+/// The first height after `height` at which cellarfees starts auctions
+pub fn next_auction_height(height: u64, parameters: &AuctionParameters) -> Option<u64> {
+    let interval = parameters.auction_interval;
+    if interval == 0 {
+        return None;
+    }
+
+    let earliest = (height + 1).max(parameters.minimum_auction_height);
+
+    Some(earliest.div_ceil(interval) * interval)
+}
+
+/// Predicts the next auction for every denom that has accrued fees or has a fee balance. usomm
+/// is never auctioned and is left out.
+pub fn upcoming_auctions(inputs: &ScheduleInputs) -> Vec<UpcomingAuction> {
+    let next_height = next_auction_height(inputs.height, &inputs.parameters);
+    let next_time = next_height.map(|h| {
+        let blocks = (h - inputs.height) as u32;
+
+        inputs.time + inputs.block_time * blocks
+    });
+
+    let mut denoms: Vec<Denom> = inputs
+        .accruals
+        .iter()
+        .map(|(denom, _)| denom.clone())
+        .chain(inputs.fee_balances.iter().map(|b| b.denom.clone()))
+        .filter(|d| !d.is_usomm())
+        .collect();
+    denoms.sort();
+    denoms.dedup();
+
+    denoms
+        .into_iter()
+        .map(|denom| {
+            let accrual_count = inputs
+                .accruals
+                .iter()
+                .find(|(d, _)| d == &denom)
+                .map(|(_, count)| *count)
+                .unwrap_or_default();
+            let fee_balance = inputs
+                .fee_balances
+                .iter()
+                .find(|b| b.denom == denom)
+                .cloned()
+                .unwrap_or_else(|| TokenAmount::zero(denom.clone()));
+            let has_active_auction = inputs.active_auction_denoms.contains(&denom);
+            let qualifies = accrual_count >= inputs.parameters.fee_accrual_auction_threshold
+                && !fee_balance.is_zero()
+                && !has_active_auction
+                && next_height.is_some();
+
+            UpcomingAuction {
+                denom,
+                accrual_count,
+                fee_balance,
+                has_active_auction,
+                qualifies,
+                expected_start_block: next_height.filter(|_| qualifies),
+                expected_start_time: next_time.filter(|_| qualifies),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs() -> ScheduleInputs {
+        ScheduleInputs {
+            height: 1_000_050,
+            time: SystemTime::UNIX_EPOCH,
+            block_time: DEFAULT_BLOCK_TIME,
+            parameters: AuctionParameters {
+                auction_interval: 100,
+                fee_accrual_auction_threshold: 2,
+                ..Default::default()
+            },
+            accruals: vec![(Denom::USDC, 3), (Denom::WETH, 1), (Denom::DAI, 5)],
+            fee_balances: vec![
+                TokenAmount::new(Denom::USDC, 1_000_000),
+                TokenAmount::new(Denom::WETH, 1_000),
+                TokenAmount::new(Denom::DAI, 1_000),
+                TokenAmount::new(Denom::WBTC, 10),
+                TokenAmount::usomm(5),
+            ],
+            active_auction_denoms: vec![Denom::DAI],
+        }
+    }
+
+    #[test]
+    fn test_next_auction_height() {
+        let mut parameters = inputs().parameters;
+
+        assert_eq!(Some(1_000_100), next_auction_height(1_000_050, &parameters));
+        assert_eq!(Some(1_000_200), next_auction_height(1_000_100, &parameters));
+
+        parameters.minimum_auction_height = 2_000_001;
+        assert_eq!(Some(2_000_100), next_auction_height(1_000_050, &parameters));
+
+        parameters.auction_interval = 0;
+        assert_eq!(None, next_auction_height(1_000_050, &parameters));
+    }
+
+    #[test]
+    fn test_upcoming_auctions() {
+        let upcoming = upcoming_auctions(&inputs());
+        let get = |denom: &Denom| upcoming.iter().find(|u| &u.denom == denom).unwrap();
+
+        assert_eq!(4, upcoming.len());
+
+        let usdc = get(&Denom::USDC);
+        assert!(usdc.qualifies);
+        assert_eq!(Some(1_000_100), usdc.expected_start_block);
+        assert_eq!(
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(300)),
+            usdc.expected_start_time
+        );
+        assert_eq!(1_000_000, usdc.fee_balance.raw);
+
+        // below the accrual threshold
+        assert!(!get(&Denom::WETH).qualifies);
+        assert_eq!(None, get(&Denom::WETH).expected_start_block);
+        // already being auctioned
+        assert!(get(&Denom::DAI).has_active_auction);
+        assert!(!get(&Denom::DAI).qualifies);
+        // a balance but no accruals
+        assert_eq!(0, get(&Denom::WBTC).accrual_count);
+        assert!(!get(&Denom::WBTC).qualifies);
+    }
+}
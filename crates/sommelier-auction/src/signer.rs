@@ -0,0 +1,287 @@
+//! Signing abstractions for bid transactions.
+//!
+//! [`BidSigner`] decouples transaction construction from key custody. It is implemented for
+//! [`AccountInfo`] for local keys and for [`RemoteSigner`], which delegates signing to an external
+//! process over a TCP or Unix socket.
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use async_trait::async_trait;
+use eyre::{eyre, Result};
+use ocular::{
+    chain::ChainContext,
+    cosmrs::{
+        crypto::{secp256k1::VerifyingKey, PublicKey},
+        proto::cosmos::tx::v1beta1::TxRaw,
+        tx::{Fee, Raw, SignDoc, SignerInfo},
+    },
+    tx::UnsignedTx,
+};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::AccountInfo;
+
+/// Default amount of time to wait for a remote signer to respond
+pub const DEFAULT_REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The result of signing a sign doc
+#[derive(Debug, Clone)]
+pub struct SignerOutput {
+    /// 64 byte compact secp256k1 signature over the sign doc bytes
+    pub signature: Vec<u8>,
+    /// The public key corresponding to the key that produced the signature
+    pub public_key: PublicKey,
+}
+
+/// Something capable of signing bid transactions. Implementors receive the protobuf encoded
+/// `SignDoc` bytes of a `SIGN_MODE_DIRECT` transaction.
+///
+/// The returned futures are not required to be `Send` because [`AccountInfo`]'s signing key
+/// isn't thread safe.
+#[async_trait(?Send)]
+pub trait BidSigner {
+    /// The public key of the signing account. This is included in the transaction's signer info
+    /// before the sign doc is built.
+    async fn public_key(&self) -> Result<PublicKey>;
+
+    /// Sign the provided sign doc bytes
+    async fn sign(&self, sign_doc: &[u8]) -> Result<SignerOutput>;
+
+    /// Gets the bech32 address of the signing account with the given prefix
+    async fn address(&self, prefix: &str) -> Result<String> {
+        Ok(self.public_key().await?.account_id(prefix)?.to_string())
+    }
+}
+
+#[async_trait(?Send)]
+impl BidSigner for AccountInfo {
+    async fn public_key(&self) -> Result<PublicKey> {
+        Ok(AccountInfo::public_key(self))
+    }
+
+    async fn sign(&self, sign_doc: &[u8]) -> Result<SignerOutput> {
+        let signature = self.private_key().sign(sign_doc)?;
+
+        Ok(SignerOutput {
+            signature: signature.as_ref().to_vec(),
+            public_key: AccountInfo::public_key(self),
+        })
+    }
+}
+
+/// Where a [`RemoteSigner`] can be reached
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RemoteSignerEndpoint {
+    /// A TCP address such as `127.0.0.1:9500`
+    Tcp(String),
+    /// A path to a Unix domain socket
+    Unix(PathBuf),
+}
+
+impl FromStr for RemoteSignerEndpoint {
+    type Err = eyre::Report;
+
+    /// Parses `tcp://host:port` or `unix:///path/to/socket`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            return Ok(RemoteSignerEndpoint::Tcp(addr.to_string()));
+        }
+
+        if let Some(path) = s.strip_prefix("unix://") {
+            return Ok(RemoteSignerEndpoint::Unix(PathBuf::from(path)));
+        }
+
+        Err(eyre!(
+            "invalid remote signer endpoint {s}. expected tcp://host:port or unix:///path"
+        ))
+    }
+}
+
+/// A request sent to a remote signer. Messages are newline delimited JSON; each connection
+/// carries a single request and response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum RemoteSignerRequest {
+    /// Request the signer's public key
+    PublicKey,
+    /// Request a signature over hex encoded sign doc bytes
+    Sign { sign_doc: String },
+}
+
+/// A response from a remote signer. Byte fields are hex encoded. The public key is the 33 byte
+/// compressed SEC1 encoding.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteSignerResponse {
+    #[serde(default)]
+    pub public_key: Option<String>,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A [`BidSigner`] that forwards sign requests to an external process, for example a service
+/// backed by a KMS.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    endpoint: RemoteSignerEndpoint,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    /// Construct a [`RemoteSigner`] for the given endpoint
+    pub fn new(endpoint: RemoteSignerEndpoint) -> Self {
+        Self {
+            endpoint,
+            timeout: DEFAULT_REMOTE_SIGNER_TIMEOUT,
+        }
+    }
+
+    /// Set how long to wait for the remote signer to respond
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Gets the endpoint
+    pub fn endpoint(&self) -> &RemoteSignerEndpoint {
+        &self.endpoint
+    }
+
+    async fn request(&self, request: &RemoteSignerRequest) -> Result<RemoteSignerResponse> {
+        let call = async {
+            match &self.endpoint {
+                RemoteSignerEndpoint::Tcp(addr) => {
+                    let stream = tokio::net::TcpStream::connect(addr).await?;
+                    exchange(stream, request).await
+                }
+                #[cfg(unix)]
+                RemoteSignerEndpoint::Unix(path) => {
+                    let stream = tokio::net::UnixStream::connect(path).await?;
+                    exchange(stream, request).await
+                }
+                #[cfg(not(unix))]
+                RemoteSignerEndpoint::Unix(_) => Err(eyre!(
+                    "unix socket signers are not supported on this platform"
+                )),
+            }
+        };
+
+        let response = tokio::time::timeout(self.timeout, call)
+            .await
+            .map_err(|_| eyre!("remote signer did not respond within {:?}", self.timeout))??;
+
+        if let Some(err) = response.error {
+            return Err(eyre!("remote signer returned an error: {err}"));
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait(?Send)]
+impl BidSigner for RemoteSigner {
+    async fn public_key(&self) -> Result<PublicKey> {
+        let response = self.request(&RemoteSignerRequest::PublicKey).await?;
+
+        match response.public_key {
+            Some(key) => decode_public_key(&key),
+            None => Err(eyre!("remote signer response is missing a public key")),
+        }
+    }
+
+    async fn sign(&self, sign_doc: &[u8]) -> Result<SignerOutput> {
+        let request = RemoteSignerRequest::Sign {
+            sign_doc: hex::encode(sign_doc),
+        };
+        let response = self.request(&request).await?;
+
+        let public_key = match response.public_key {
+            Some(key) => decode_public_key(&key)?,
+            None => return Err(eyre!("remote signer response is missing a public key")),
+        };
+        let signature = match response.signature {
+            Some(sig) => hex::decode(sig)?,
+            None => return Err(eyre!("remote signer response is missing a signature")),
+        };
+        if signature.len() != 64 {
+            return Err(eyre!(
+                "remote signer returned a {} byte signature, expected 64",
+                signature.len()
+            ));
+        }
+
+        Ok(SignerOutput {
+            signature,
+            public_key,
+        })
+    }
+}
+
+/// Decodes a hex encoded SEC1 secp256k1 public key
+pub fn decode_public_key(key: &str) -> Result<PublicKey> {
+    let bytes = hex::decode(key)?;
+    let key = VerifyingKey::from_sec1_bytes(&bytes)
+        .map_err(|e| eyre!("invalid secp256k1 public key: {e}"))?;
+
+    Ok(PublicKey::from(key))
+}
+
+async fn exchange<S>(stream: S, request: &RemoteSignerRequest) -> Result<RemoteSignerResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+
+    let mut response = String::new();
+    BufReader::new(reader).read_line(&mut response).await?;
+    if response.is_empty() {
+        return Err(eyre!(
+            "remote signer closed the connection without responding"
+        ));
+    }
+
+    Ok(serde_json::from_str(&response)?)
+}
+
+/// Signs a transaction body in `SIGN_MODE_DIRECT` with any [`BidSigner`], producing a raw
+/// transaction ready for broadcast.
+pub async fn sign_tx<S>(
+    signer: &S,
+    unsigned_tx: UnsignedTx,
+    fee: Fee,
+    chain_context: &ChainContext,
+    account_number: u64,
+    sequence: u64,
+) -> Result<Raw>
+where
+    S: BidSigner + ?Sized,
+{
+    let public_key = signer.public_key().await?;
+    let auth_info = SignerInfo::single_direct(Some(public_key), sequence).auth_info(fee);
+    let chain_id = ocular::cosmrs::tendermint::chain::Id::try_from(chain_context.id.to_owned())?;
+    let tx_body = unsigned_tx.into_inner().finish();
+    let sign_doc = SignDoc::new(&tx_body, &auth_info, &chain_id, account_number)
+        .map_err(|e| eyre!("failed to create SignDoc for chain {chain_id}: {e}"))?;
+    let body_bytes = sign_doc.body_bytes.clone();
+    let auth_info_bytes = sign_doc.auth_info_bytes.clone();
+    let sign_doc_bytes = sign_doc.into_bytes()?;
+
+    let output = signer.sign(&sign_doc_bytes).await?;
+    if output.public_key != public_key {
+        return Err(eyre!(
+            "signer returned a signature from a different key than the one in the signer info"
+        ));
+    }
+
+    Ok(TxRaw {
+        body_bytes,
+        auth_info_bytes,
+        signatures: vec![output.signature],
+    }
+    .into())
+}
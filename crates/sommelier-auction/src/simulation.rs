@@ -0,0 +1,404 @@
+//! An off-chain replica of how the auction module fills a bid, for previewing the outcome of a
+//! [`Bid`] before submitting it.
+//!
+//! The auction module handles a bid in this order:
+//!
+//! 1. The bid's sale token must match the auction's.
+//! 2. The minimum amount sought can't exceed the tokens remaining in the auction.
+//! 3. Buying the minimum amount at the current unit price, rounded up to a whole usomm, must not
+//!    cost more than the maximum bid.
+//! 4. The fill is the number of whole tokens the maximum bid affords at the unit price, capped by
+//!    the remaining supply.
+//! 5. The bidder pays the unit price times the fill, rounded up to a whole usomm.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    amount::TokenAmount,
+    auction::Auction,
+    bid::Bid,
+    dec::Dec,
+    view::{AuctionView, BidView},
+};
+
+/// The outcome of submitting a bid to an auction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimulatedFill {
+    Filled(Fill),
+    Rejected(BidRejection),
+}
+
+impl SimulatedFill {
+    pub fn fill(&self) -> Option<&Fill> {
+        match self {
+            SimulatedFill::Filled(fill) => Some(fill),
+            SimulatedFill::Rejected(_) => None,
+        }
+    }
+
+    pub fn rejection(&self) -> Option<&BidRejection> {
+        match self {
+            SimulatedFill::Filled(_) => None,
+            SimulatedFill::Rejected(rejection) => Some(rejection),
+        }
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.fill().is_some()
+    }
+}
+
+/// A successful fill, with the same amounts the chain records on the resulting bid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fill {
+    pub auction_id: u32,
+    /// The unit price the bid was filled at
+    pub unit_price_in_usomm: Dec,
+    pub total_fulfilled_sale_tokens: TokenAmount,
+    pub total_usomm_paid: TokenAmount,
+    /// The tokens left in the auction after this bid
+    pub remaining_tokens_for_sale: TokenAmount,
+    /// Whether the remaining supply limited the fill to less than the maximum bid affords
+    pub supply_capped: bool,
+}
+
+impl Fill {
+    /// Whether this bid buys out the auction, which ends it
+    pub fn finishes_auction(&self) -> bool {
+        self.remaining_tokens_for_sale.is_zero()
+    }
+
+    /// Whether a bid recorded on chain has the same outcome as this simulation
+    pub fn matches(&self, recorded: &BidView) -> bool {
+        self.auction_id == recorded.auction_id
+            && self.unit_price_in_usomm == recorded.sale_token_unit_price_in_usomm
+            && self.total_fulfilled_sale_tokens == recorded.total_fulfilled_sale_tokens
+            && self.total_usomm_paid == recorded.total_usomm_paid
+    }
+}
+
+/// Why the auction module would reject a bid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum BidRejection {
+    /// The auction message couldn't be read
+    InvalidAuction {
+        error: String,
+    },
+    AuctionEnded {
+        auction_id: u32,
+    },
+    AuctionMismatch {
+        auction_id: u32,
+        bid_auction_id: u32,
+    },
+    /// The maximum bid isn't denominated in usomm
+    InvalidBidDenom {
+        maximum_usomm_in: TokenAmount,
+    },
+    /// The bid is for a different token than the auction is selling
+    DenomMismatch {
+        auction_denom: String,
+        bid: TokenAmount,
+    },
+    /// The bid seeks more tokens than the auction has left
+    MinimumExceedsSupply {
+        minimum_tokens_out: TokenAmount,
+        remaining_tokens_for_sale: TokenAmount,
+    },
+    /// The maximum bid can't pay for the minimum amount sought at the current price
+    InsufficientBid {
+        minimum_purchase_price: TokenAmount,
+        maximum_usomm_in: TokenAmount,
+    },
+    /// The amounts involved don't fit in the types used for the math
+    Overflow,
+}
+
+impl std::fmt::Display for BidRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BidRejection::InvalidAuction { error } => write!(f, "invalid auction: {error}"),
+            BidRejection::AuctionEnded { auction_id } => {
+                write!(f, "auction {auction_id} has ended")
+            }
+            BidRejection::AuctionMismatch {
+                auction_id,
+                bid_auction_id,
+            } => write!(f, "bid is for auction {bid_auction_id}, not {auction_id}"),
+            BidRejection::InvalidBidDenom { maximum_usomm_in } => {
+                write!(f, "maximum bid {maximum_usomm_in} is not in usomm")
+            }
+            BidRejection::DenomMismatch { auction_denom, bid } => {
+                write!(f, "bid for {bid} but the auction is selling {auction_denom}")
+            }
+            BidRejection::MinimumExceedsSupply {
+                minimum_tokens_out,
+                remaining_tokens_for_sale,
+            } => write!(
+                f,
+                "minimum tokens out {minimum_tokens_out} exceeds remaining supply {remaining_tokens_for_sale}"
+            ),
+            BidRejection::InsufficientBid {
+                minimum_purchase_price,
+                maximum_usomm_in,
+            } => write!(
+                f,
+                "maximum bid {maximum_usomm_in} can't pay {minimum_purchase_price} for the minimum tokens out"
+            ),
+            BidRejection::Overflow => write!(f, "bid amounts overflowed"),
+        }
+    }
+}
+
+/// Simulates submitting `bid` to `auction` at its current unit price
+pub fn simulate_bid(auction: &Auction, bid: &Bid) -> SimulatedFill {
+    match AuctionView::try_from(auction) {
+        Ok(view) => simulate_bid_at_price(&view, bid, view.current_unit_price_in_usomm),
+        Err(err) => SimulatedFill::Rejected(BidRejection::InvalidAuction {
+            error: err.to_string(),
+        }),
+    }
+}
+
+/// Simulates submitting `bid` to `auction` when its unit price is `unit_price_in_usomm`, i.e. a
+/// price predicted for a later block by a [`crate::price_curve::PriceCurve`]
+pub fn simulate_bid_at_price(
+    auction: &AuctionView,
+    bid: &Bid,
+    unit_price_in_usomm: Dec,
+) -> SimulatedFill {
+    match fill(auction, bid, unit_price_in_usomm) {
+        Ok(fill) => SimulatedFill::Filled(fill),
+        Err(rejection) => SimulatedFill::Rejected(rejection),
+    }
+}
+
+fn fill(auction: &AuctionView, bid: &Bid, unit_price: Dec) -> Result<Fill, BidRejection> {
+    if !auction.is_active() || !unit_price.is_positive() {
+        return Err(BidRejection::AuctionEnded {
+            auction_id: auction.id,
+        });
+    }
+    if bid.auction_id != auction.id {
+        return Err(BidRejection::AuctionMismatch {
+            auction_id: auction.id,
+            bid_auction_id: bid.auction_id,
+        });
+    }
+    if !bid.maximum_usomm_in.denom.is_usomm() {
+        return Err(BidRejection::InvalidBidDenom {
+            maximum_usomm_in: bid.maximum_usomm_in.clone(),
+        });
+    }
+
+    let denom = auction.fee_token();
+    if &bid.minimum_tokens_out.denom != denom || &bid.fee_token != denom {
+        return Err(BidRejection::DenomMismatch {
+            auction_denom: denom.to_string(),
+            bid: bid.minimum_tokens_out.clone(),
+        });
+    }
+
+    let remaining = &auction.remaining_tokens_for_sale;
+    if bid.minimum_tokens_out.raw > remaining.raw {
+        return Err(BidRejection::MinimumExceedsSupply {
+            minimum_tokens_out: bid.minimum_tokens_out.clone(),
+            remaining_tokens_for_sale: remaining.clone(),
+        });
+    }
+
+    // the chain rounds purchase prices up so rounding can't be exploited
+    let minimum_purchase_price = cost(unit_price, bid.minimum_tokens_out.raw)?;
+    if minimum_purchase_price > bid.maximum_usomm_in.raw {
+        return Err(BidRejection::InsufficientBid {
+            minimum_purchase_price: TokenAmount::usomm(minimum_purchase_price),
+            maximum_usomm_in: bid.maximum_usomm_in.clone(),
+        });
+    }
+
+    let affordable = to_dec(bid.maximum_usomm_in.raw)?
+        .checked_div(unit_price)
+        .ok_or(BidRejection::Overflow)?
+        .truncate() as u128;
+    let fulfilled = affordable.min(remaining.raw);
+    let paid = cost(unit_price, fulfilled)?;
+
+    Ok(Fill {
+        auction_id: auction.id,
+        unit_price_in_usomm: unit_price,
+        total_fulfilled_sale_tokens: TokenAmount::new(denom.clone(), fulfilled),
+        total_usomm_paid: TokenAmount::usomm(paid),
+        remaining_tokens_for_sale: TokenAmount::new(denom.clone(), remaining.raw - fulfilled),
+        supply_capped: fulfilled < affordable,
+    })
+}
+
+/// The usomm cost of `amount` tokens at `unit_price`, rounded up
+fn cost(unit_price: Dec, amount: u128) -> Result<u128, BidRejection> {
+    let cost = to_dec(amount)?
+        .checked_mul(unit_price)
+        .ok_or(BidRejection::Overflow)?
+        .ceil();
+
+    u128::try_from(cost).map_err(|_| BidRejection::Overflow)
+}
+
+fn to_dec(amount: u128) -> Result<Dec, BidRejection> {
+    i128::try_from(amount)
+        .ok()
+        .and_then(Dec::from_int)
+        .ok_or(BidRejection::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auction::Bid as ProtoBid,
+        denom::Denom,
+        fixtures::{self, bid, coin, recorded_bid},
+    };
+
+    // 1.5 usomm per base unit of USDC, 1000 units left
+    fn auction() -> Auction {
+        fixtures::auction(5000, 1000)
+    }
+
+    #[test]
+    fn test_fill() {
+        // 1000 usomm affords 666.67 units, truncated to 666, which costs 999 usomm
+        let fill = simulate_bid(&auction(), &bid(1000, 100));
+        let fill = fill.fill().unwrap();
+
+        assert_eq!(666, fill.total_fulfilled_sale_tokens.raw);
+        assert_eq!(TokenAmount::usomm(999), fill.total_usomm_paid);
+        assert_eq!(334, fill.remaining_tokens_for_sale.raw);
+        assert!(!fill.supply_capped);
+        assert!(!fill.finishes_auction());
+    }
+
+    #[test]
+    fn test_supply_capped_fill() {
+        let fill = simulate_bid(&auction(), &bid(10_000, 100));
+        let fill = fill.fill().unwrap();
+
+        assert_eq!(1000, fill.total_fulfilled_sale_tokens.raw);
+        assert_eq!(TokenAmount::usomm(1500), fill.total_usomm_paid);
+        assert!(fill.supply_capped);
+        assert!(fill.finishes_auction());
+    }
+
+    #[test]
+    fn test_rounds_cost_up() {
+        // 3 units at 1.5 usomm cost 4.5, rounded up to 5
+        let fill = simulate_bid(&auction(), &bid(5, 3));
+        assert_eq!(TokenAmount::usomm(5), fill.fill().unwrap().total_usomm_paid);
+
+        let rejected = simulate_bid(&auction(), &bid(4, 3));
+        assert_eq!(
+            Some(&BidRejection::InsufficientBid {
+                minimum_purchase_price: TokenAmount::usomm(5),
+                maximum_usomm_in: TokenAmount::usomm(4),
+            }),
+            rejected.rejection()
+        );
+    }
+
+    #[test]
+    fn test_rejections() {
+        assert!(matches!(
+            simulate_bid(&auction(), &bid(10_000, 1001)).rejection(),
+            Some(BidRejection::MinimumExceedsSupply { .. })
+        ));
+
+        let mut weth_bid = bid(1000, 1);
+        weth_bid.fee_token = Denom::WETH;
+        weth_bid.minimum_tokens_out = TokenAmount::new(Denom::WETH, 1);
+        assert!(matches!(
+            simulate_bid(&auction(), &weth_bid).rejection(),
+            Some(BidRejection::DenomMismatch { .. })
+        ));
+
+        let ended = Auction {
+            end_block: 200,
+            ..auction()
+        };
+        assert!(matches!(
+            simulate_bid(&ended, &bid(1000, 1)).rejection(),
+            Some(BidRejection::AuctionEnded { .. })
+        ));
+
+        let mut other_auction = bid(1000, 1);
+        other_auction.auction_id = 8;
+        assert!(matches!(
+            simulate_bid(&auction(), &other_auction).rejection(),
+            Some(BidRejection::AuctionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_matches_recorded_bid() {
+        let recorded = recorded_bid(&bid(1000, 100), 666, "1500000000000000000", 999);
+        let fill = simulate_bid(&auction(), &bid(1000, 100));
+        let fill = fill.fill().unwrap();
+
+        assert!(fill.matches(&BidView::try_from(recorded.clone()).unwrap()));
+
+        // any difference in the outcome is a mismatch
+        let paid_more = ProtoBid {
+            total_usomm_paid: coin(1000, &Denom::USOMM),
+            ..recorded.clone()
+        };
+        let fewer_tokens = ProtoBid {
+            total_fulfilled_sale_tokens: coin(665, &Denom::USDC),
+            ..recorded.clone()
+        };
+        let other_price = ProtoBid {
+            sale_token_unit_price_in_usomm: "1400000000000000000".to_string(),
+            ..recorded.clone()
+        };
+        let other_auction = ProtoBid {
+            auction_id: 8,
+            ..recorded
+        };
+        for recorded in [paid_more, fewer_tokens, other_price, other_auction] {
+            assert!(!fill.matches(&BidView::try_from(recorded).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_shrunk_bid_can_be_filled() {
+        // the most a bid can ask for at 3 usomm per unit, as the threshold strategy bids
+        let view = AuctionView::try_from(auction()).unwrap();
+        let price: Dec = "3".parse().unwrap();
+        let original = bid(1000, 333);
+        assert!(simulate_bid_at_price(&view, &original, price).is_filled());
+
+        let shrunk = original.scaled_to(500).unwrap();
+        assert_eq!(166, shrunk.minimum_tokens_out.raw);
+        let fill = simulate_bid_at_price(&view, &shrunk, price);
+        assert_eq!(
+            TokenAmount::usomm(498),
+            fill.fill().unwrap().total_usomm_paid
+        );
+
+        for shrunk in (1..1000).filter_map(|maximum| original.scaled_to(maximum)) {
+            assert!(
+                simulate_bid_at_price(&view, &shrunk, price).is_filled(),
+                "{shrunk:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_predicted_price() {
+        let view = AuctionView::try_from(auction()).unwrap();
+        let fill = simulate_bid_at_price(&view, &bid(1000, 100), "1.25".parse().unwrap());
+
+        assert_eq!(800, fill.fill().unwrap().total_fulfilled_sale_tokens.raw);
+        assert_eq!(
+            TokenAmount::usomm(1000),
+            fill.fill().unwrap().total_usomm_paid
+        );
+    }
+}
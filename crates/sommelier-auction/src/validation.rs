@@ -0,0 +1,228 @@
+//! Pre-flight checks of a [`Bid`] against an auction and the auction module's parameters, so bids
+//! the chain would reject aren't signed and paid for.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    amount::TokenAmount,
+    auction::{Auction, TokenPrice},
+    bid::Bid,
+    dec::Dec,
+    denom::Denom,
+    parameters::AuctionParameters,
+    simulation::{simulate_bid, BidRejection},
+    view::TokenPriceView,
+};
+
+/// A rule a bid breaks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "violation", rename_all = "snake_case")]
+pub enum BidViolation {
+    /// The maximum bid is below the `minimum_bid_in_usomm` parameter
+    BelowMinimumBid {
+        maximum_usomm_in: TokenAmount,
+        minimum_bid_in_usomm: u64,
+    },
+    /// The minimum tokens sought are worth less than the `minimum_sale_tokens_usd_value`
+    /// parameter, and don't buy out the rest of the auction either
+    BelowMinimumUsdValue {
+        usd_value: Dec,
+        minimum: Dec,
+    },
+    /// The auction module has no price for the fee token, so the USD value can't be checked
+    MissingTokenPrice {
+        denom: Denom,
+    },
+    InvalidTokenPrice {
+        denom: Denom,
+        error: String,
+    },
+    /// The auction would reject the bid regardless of parameters
+    Rejected(BidRejection),
+}
+
+impl std::fmt::Display for BidViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BidViolation::BelowMinimumBid {
+                maximum_usomm_in,
+                minimum_bid_in_usomm,
+            } => write!(
+                f,
+                "maximum bid {maximum_usomm_in} is below the minimum bid of {minimum_bid_in_usomm}usomm"
+            ),
+            BidViolation::BelowMinimumUsdValue { usd_value, minimum } => write!(
+                f,
+                "minimum tokens out are worth ${usd_value}, below the minimum of ${minimum}"
+            ),
+            BidViolation::MissingTokenPrice { denom } => {
+                write!(f, "no token price for {denom}")
+            }
+            BidViolation::InvalidTokenPrice { denom, error } => {
+                write!(f, "invalid token price for {denom}: {error}")
+            }
+            BidViolation::Rejected(rejection) => write!(f, "{rejection}"),
+        }
+    }
+}
+
+/// The error returned when submitting a bid that fails validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BidValidationError {
+    pub violations: Vec<BidViolation>,
+}
+
+impl std::fmt::Display for BidValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let violations = self
+            .violations
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>();
+
+        write!(f, "invalid bid: {}", violations.join("; "))
+    }
+}
+
+impl std::error::Error for BidValidationError {}
+
+impl Bid {
+    /// Checks the bid against `auction` and the auction module's parameters, returning every rule
+    /// it breaks. The USD value rule uses the fee token's on-chain price from `token_prices`, as
+    /// the chain does.
+    pub fn validate(
+        &self,
+        auction: &Auction,
+        parameters: &AuctionParameters,
+        token_prices: &[TokenPrice],
+    ) -> Vec<BidViolation> {
+        let mut violations = Vec::new();
+
+        if self.maximum_usomm_in.raw < parameters.minimum_bid_in_usomm as u128 {
+            violations.push(BidViolation::BelowMinimumBid {
+                maximum_usomm_in: self.maximum_usomm_in.clone(),
+                minimum_bid_in_usomm: parameters.minimum_bid_in_usomm,
+            });
+        }
+
+        let buys_out_auction = auction
+            .remaining_tokens_for_sale
+            .as_ref()
+            .and_then(|remaining| remaining.amount.parse::<u128>().ok())
+            .is_some_and(|remaining| remaining == self.minimum_tokens_out.raw);
+        if !buys_out_auction {
+            if let Some(violation) =
+                self.usd_value_violation(parameters.minimum_sale_tokens_usd_value, token_prices)
+            {
+                violations.push(violation);
+            }
+        }
+
+        if let Some(rejection) = simulate_bid(auction, self).rejection() {
+            violations.push(BidViolation::Rejected(rejection.clone()));
+        }
+
+        violations
+    }
+
+    fn usd_value_violation(
+        &self,
+        minimum: Dec,
+        token_prices: &[TokenPrice],
+    ) -> Option<BidViolation> {
+        let denom = &self.minimum_tokens_out.denom;
+        let Some(price) = token_prices.iter().find(|p| p.denom == denom.as_str()) else {
+            return Some(BidViolation::MissingTokenPrice {
+                denom: denom.clone(),
+            });
+        };
+        let usd_value = match TokenPriceView::try_from(price)
+            .and_then(|price| price.usd_value(&self.minimum_tokens_out))
+        {
+            Ok(v) => v,
+            Err(err) => {
+                return Some(BidViolation::InvalidTokenPrice {
+                    denom: denom.clone(),
+                    error: err.to_string(),
+                })
+            }
+        };
+
+        if usd_value < minimum {
+            return Some(BidViolation::BelowMinimumUsdValue { usd_value, minimum });
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, bid, coin, token_price};
+
+    // 1.5 usomm per micro USDC
+    fn auction() -> Auction {
+        fixtures::auction(5_000_000_000, 1_000_000_000)
+    }
+
+    fn parameters() -> AuctionParameters {
+        AuctionParameters {
+            minimum_bid_in_usomm: 1_000_000,
+            minimum_sale_tokens_usd_value: "1".parse().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn prices() -> Vec<TokenPrice> {
+        vec![token_price(&Denom::USDC, 6, "1.0", 1)]
+    }
+
+    #[test]
+    fn test_valid_bid() {
+        let violations = bid(3_000_000, 2_000_000).validate(&auction(), &parameters(), &prices());
+
+        assert!(violations.is_empty(), "{violations:?}");
+    }
+
+    #[test]
+    fn test_violations() {
+        let violations = bid(900_000, 500_000).validate(&auction(), &parameters(), &prices());
+
+        assert_eq!(
+            vec![
+                BidViolation::BelowMinimumBid {
+                    maximum_usomm_in: TokenAmount::usomm(900_000),
+                    minimum_bid_in_usomm: 1_000_000,
+                },
+                BidViolation::BelowMinimumUsdValue {
+                    usd_value: "0.5".parse().unwrap(),
+                    minimum: "1".parse().unwrap(),
+                },
+            ],
+            violations
+        );
+
+        let violations = bid(3_000_000, 2_000_000).validate(&auction(), &parameters(), &[]);
+        assert_eq!(
+            vec![BidViolation::MissingTokenPrice { denom: Denom::USDC }],
+            violations
+        );
+
+        let violations = bid(1_000_000, 2_000_000).validate(&auction(), &parameters(), &prices());
+        assert!(matches!(
+            violations.as_slice(),
+            [BidViolation::Rejected(BidRejection::InsufficientBid { .. })]
+        ));
+    }
+
+    #[test]
+    fn test_buyout_skips_usd_value() {
+        let auction = Auction {
+            remaining_tokens_for_sale: coin(500_000, &Denom::USDC),
+            ..auction()
+        };
+        let violations = bid(1_000_000, 500_000).validate(&auction, &parameters(), &prices());
+
+        assert!(violations.is_empty(), "{violations:?}");
+    }
+}
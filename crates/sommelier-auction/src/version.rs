@@ -0,0 +1,100 @@
+//! Types for checking that a node runs a Sommelier version this crate's protos are compatible with.
+use serde::{Deserialize, Serialize};
+
+use crate::cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetNodeInfoResponse;
+
+/// The Sommelier major version the bundled proto bindings were generated from
+pub const SUPPORTED_SOMMELIER_MAJOR_VERSION: u64 = 7;
+
+/// What to do when a node reports a Sommelier major version other than
+/// [`SUPPORTED_SOMMELIER_MAJOR_VERSION`], or when the version can't be determined.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionCheck {
+    /// Log a warning and continue
+    #[default]
+    Warn,
+    /// Return an error from client construction
+    Fail,
+    /// Don't query the node's version at all
+    Skip,
+}
+
+/// Version information reported by a node through the `cosmos.base.tendermint` service
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeInfo {
+    /// The chain ID the node is connected to
+    pub network: String,
+    /// The node's moniker
+    pub moniker: String,
+    /// The application binary name, i.e. "sommelier"
+    pub app_name: String,
+    /// The application version string, i.e. "v7.0.1"
+    pub version: String,
+    /// The commit the application was built from
+    pub git_commit: String,
+    /// The version of the cosmos-sdk module the application was built with, if reported
+    pub cosmos_sdk_version: Option<String>,
+}
+
+impl NodeInfo {
+    /// The major component of the application version, if it can be parsed
+    pub fn major_version(&self) -> Option<u64> {
+        parse_major_version(&self.version)
+    }
+
+    /// Whether the node runs the Sommelier major version this crate supports
+    pub fn is_supported(&self) -> bool {
+        self.major_version() == Some(SUPPORTED_SOMMELIER_MAJOR_VERSION)
+    }
+}
+
+impl From<GetNodeInfoResponse> for NodeInfo {
+    fn from(response: GetNodeInfoResponse) -> Self {
+        let mut info = NodeInfo::default();
+
+        if let Some(node_info) = response.default_node_info {
+            info.network = node_info.network;
+            info.moniker = node_info.moniker;
+        }
+
+        if let Some(version) = response.application_version {
+            info.app_name = version.app_name;
+            info.version = version.version;
+            info.git_commit = version.git_commit;
+            info.cosmos_sdk_version = version
+                .build_deps
+                .into_iter()
+                .find(|m| m.path == "github.com/cosmos/cosmos-sdk")
+                .map(|m| m.version);
+        }
+
+        info
+    }
+}
+
+/// Parses the major version out of strings like "v7.0.1", "7.1.0" or "v8.0.0-rc1"
+pub fn parse_major_version(version: &str) -> Option<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_major_version() {
+        assert_eq!(parse_major_version("v7.0.1"), Some(7));
+        assert_eq!(parse_major_version("7.1.0"), Some(7));
+        assert_eq!(parse_major_version("v8.0.0-rc1"), Some(8));
+        assert_eq!(parse_major_version("v8"), Some(8));
+        assert_eq!(parse_major_version(""), None);
+        assert_eq!(parse_major_version("main-abc123"), None);
+    }
+}
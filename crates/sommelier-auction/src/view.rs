@@ -0,0 +1,492 @@
+//! Validated, typed views of the auction module's [`Auction`], [`ProtoBid`] and [`TokenPrice`]
+//! messages.
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    amount::TokenAmount,
+    auction::{Auction, Bid as ProtoBid, TokenPrice},
+    bid::Bid,
+    cosmos_sdk_proto::cosmos::base::v1beta1::Coin,
+    dec::{parse_field, Dec},
+    denom::Denom,
+};
+
+/// Whether an auction is still accepting bids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuctionStatus {
+    Active,
+    Ended,
+}
+
+/// A fee auction with typed fields
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionView {
+    pub id: u32,
+    pub status: AuctionStatus,
+    pub starting_tokens_for_sale: TokenAmount,
+    pub remaining_tokens_for_sale: TokenAmount,
+    pub start_block: u64,
+    /// The block the auction ended at, if it has ended
+    pub end_block: Option<u64>,
+    pub initial_price_decrease_rate: Dec,
+    pub current_price_decrease_rate: Dec,
+    /// Number of blocks between each decrease of the unit price
+    pub price_decrease_block_interval: u64,
+    /// Price in usomm of one base unit of the token for sale when the auction started
+    pub initial_unit_price_in_usomm: Dec,
+    /// Current price in usomm of one base unit of the token for sale
+    pub current_unit_price_in_usomm: Dec,
+    pub funding_module_account: String,
+    pub proceeds_module_account: String,
+}
+
+impl AuctionView {
+    /// The denom of the token being auctioned
+    pub fn fee_token(&self) -> &Denom {
+        &self.starting_tokens_for_sale.denom
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == AuctionStatus::Active
+    }
+
+    /// The amount of the fee token sold so far
+    pub fn tokens_sold(&self) -> TokenAmount {
+        TokenAmount::new(
+            self.fee_token().clone(),
+            self.starting_tokens_for_sale.raw - self.remaining_tokens_for_sale.raw,
+        )
+    }
+}
+
+impl TryFrom<Auction> for AuctionView {
+    type Error = eyre::Report;
+
+    fn try_from(auction: Auction) -> Result<Self, Self::Error> {
+        AuctionView::try_from(&auction)
+    }
+}
+
+impl TryFrom<&Auction> for AuctionView {
+    type Error = eyre::Report;
+
+    fn try_from(auction: &Auction) -> Result<Self, Self::Error> {
+        let id = auction.id;
+        let starting_tokens_for_sale = required_coin(
+            &auction.starting_tokens_for_sale,
+            "starting_tokens_for_sale",
+        )
+        .map_err(|err| eyre!("invalid auction {id}: {err}"))?;
+        let remaining_tokens_for_sale = required_coin(
+            &auction.remaining_tokens_for_sale,
+            "remaining_tokens_for_sale",
+        )
+        .map_err(|err| eyre!("invalid auction {id}: {err}"))?;
+
+        if starting_tokens_for_sale.denom != remaining_tokens_for_sale.denom {
+            return Err(eyre!(
+                "invalid auction {id}: starting tokens {starting_tokens_for_sale} and remaining tokens {remaining_tokens_for_sale} have different denoms"
+            ));
+        }
+        if remaining_tokens_for_sale.raw > starting_tokens_for_sale.raw {
+            return Err(eyre!(
+                "invalid auction {id}: more tokens remaining ({remaining_tokens_for_sale}) than were for sale ({starting_tokens_for_sale})"
+            ));
+        }
+
+        // the chain leaves end_block unset until the auction finishes
+        let (status, end_block) = match auction.end_block {
+            0 => (AuctionStatus::Active, None),
+            end_block => (AuctionStatus::Ended, Some(end_block)),
+        };
+        let dec = |value: &str, field: &str| {
+            parse_field(value, field).map_err(|err| eyre!("invalid auction {id}: {err}"))
+        };
+
+        Ok(Self {
+            id,
+            status,
+            starting_tokens_for_sale,
+            remaining_tokens_for_sale,
+            start_block: auction.start_block,
+            end_block,
+            initial_price_decrease_rate: dec(
+                &auction.initial_price_decrease_rate,
+                "initial_price_decrease_rate",
+            )?,
+            current_price_decrease_rate: dec(
+                &auction.current_price_decrease_rate,
+                "current_price_decrease_rate",
+            )?,
+            price_decrease_block_interval: auction.price_decrease_block_interval,
+            initial_unit_price_in_usomm: dec(
+                &auction.initial_unit_price_in_usomm,
+                "initial_unit_price_in_usomm",
+            )?,
+            current_unit_price_in_usomm: dec(
+                &auction.current_unit_price_in_usomm,
+                "current_unit_price_in_usomm",
+            )?,
+            funding_module_account: auction.funding_module_account.clone(),
+            proceeds_module_account: auction.proceeds_module_account.clone(),
+        })
+    }
+}
+
+/// A bid recorded on chain, with typed fields
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BidView {
+    pub id: u64,
+    pub auction_id: u32,
+    /// Address of the account that submitted the bid
+    pub bidder: String,
+    pub max_bid_in_usomm: TokenAmount,
+    pub sale_token_minimum_amount: TokenAmount,
+    /// The amount of the sale token the bid received
+    pub total_fulfilled_sale_tokens: TokenAmount,
+    /// Price in usomm per base unit of the sale token the bid was filled at
+    pub sale_token_unit_price_in_usomm: Dec,
+    pub total_usomm_paid: TokenAmount,
+    pub block_height: u64,
+}
+
+impl BidView {
+    /// The denom of the token bid on
+    pub fn fee_token(&self) -> &Denom {
+        &self.sale_token_minimum_amount.denom
+    }
+
+    /// Whether this is the on-chain record of `bid` placed by `bidder`
+    pub fn is_for(&self, bid: &Bid, bidder: &str) -> bool {
+        self.auction_id == bid.auction_id
+            && self.bidder == bidder
+            && self.max_bid_in_usomm == bid.maximum_usomm_in
+            && self.sale_token_minimum_amount == bid.minimum_tokens_out
+    }
+}
+
+impl TryFrom<ProtoBid> for BidView {
+    type Error = eyre::Report;
+
+    fn try_from(bid: ProtoBid) -> Result<Self, Self::Error> {
+        BidView::try_from(&bid)
+    }
+}
+
+impl TryFrom<&ProtoBid> for BidView {
+    type Error = eyre::Report;
+
+    fn try_from(bid: &ProtoBid) -> Result<Self, Self::Error> {
+        let id = bid.id;
+        let coin = |coin: &Option<Coin>, field: &str| {
+            required_coin(coin, field).map_err(|err| eyre!("invalid bid {id}: {err}"))
+        };
+        let max_bid_in_usomm = coin(&bid.max_bid_in_usomm, "max_bid_in_usomm")?;
+        let total_usomm_paid = coin(&bid.total_usomm_paid, "total_usomm_paid")?;
+        let sale_token_minimum_amount =
+            coin(&bid.sale_token_minimum_amount, "sale_token_minimum_amount")?;
+        let total_fulfilled_sale_tokens = coin(
+            &bid.total_fulfilled_sale_tokens,
+            "total_fulfilled_sale_tokens",
+        )?;
+
+        for amount in [&max_bid_in_usomm, &total_usomm_paid] {
+            if !amount.denom.is_usomm() {
+                return Err(eyre!(
+                    "invalid bid {id}: expected a usomm amount, got {amount}"
+                ));
+            }
+        }
+        if sale_token_minimum_amount.denom != total_fulfilled_sale_tokens.denom {
+            return Err(eyre!(
+                "invalid bid {id}: minimum amount {sale_token_minimum_amount} and fulfilled amount {total_fulfilled_sale_tokens} have different denoms"
+            ));
+        }
+
+        Ok(Self {
+            id,
+            auction_id: bid.auction_id,
+            bidder: bid.bidder.clone(),
+            max_bid_in_usomm,
+            sale_token_minimum_amount,
+            total_fulfilled_sale_tokens,
+            sale_token_unit_price_in_usomm: parse_field(
+                &bid.sale_token_unit_price_in_usomm,
+                "sale_token_unit_price_in_usomm",
+            )
+            .map_err(|err| eyre!("invalid bid {id}: {err}"))?,
+            total_usomm_paid,
+            block_height: bid.block_height,
+        })
+    }
+}
+
+/// The auction module's USD price of a token, with typed fields
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenPriceView {
+    pub denom: Denom,
+    /// The USD price of one whole token
+    pub usd_price: Dec,
+    /// Number of decimals between the base unit and a whole token
+    pub exponent: u8,
+    pub last_updated_block: u64,
+}
+
+impl TokenPriceView {
+    /// Number of blocks since the price was last updated, as of `height`
+    pub fn age_at(&self, height: u64) -> u64 {
+        height.saturating_sub(self.last_updated_block)
+    }
+
+    /// Whether the chain would refuse to use the price at `height`. The auction module rejects
+    /// prices updated more than `price_max_block_age` blocks ago.
+    pub fn is_stale_at(&self, height: u64, price_max_block_age: u64) -> bool {
+        self.age_at(height) > price_max_block_age
+    }
+
+    /// The USD value of `amount` at this price
+    pub fn usd_value(&self, amount: &TokenAmount) -> Result<Dec> {
+        if amount.denom != self.denom {
+            return Err(eyre!(
+                "can't value {amount} with the price of {}",
+                self.denom
+            ));
+        }
+
+        amount
+            .to_dec(self.exponent)
+            .and_then(|amount| amount.checked_mul(self.usd_price))
+            .ok_or_else(|| eyre!("overflow valuing {amount} at ${}", self.usd_price))
+    }
+
+    /// The price of one base unit of this token in base units of `other`, i.e. usomm per wei when
+    /// `other` is the price of usomm. `None` if `other`'s price is zero or on overflow.
+    pub fn unit_price_in(&self, other: &TokenPriceView) -> Option<Dec> {
+        let pow = |exponent: u8| Dec::from_int(10i128.checked_pow(exponent as u32)?);
+
+        self.usd_price
+            .checked_div(other.usd_price)?
+            .checked_mul(pow(other.exponent)?)?
+            .checked_div(pow(self.exponent)?)
+    }
+
+    /// The price's freshness as of `height`
+    pub fn quote_at(self, height: u64, price_max_block_age: u64) -> TokenPriceQuote {
+        TokenPriceQuote {
+            age_in_blocks: self.age_at(height),
+            is_stale: self.is_stale_at(height, price_max_block_age),
+            height,
+            price: self,
+        }
+    }
+}
+
+impl TryFrom<TokenPrice> for TokenPriceView {
+    type Error = eyre::Report;
+
+    fn try_from(price: TokenPrice) -> Result<Self, Self::Error> {
+        TokenPriceView::try_from(&price)
+    }
+}
+
+impl TryFrom<&TokenPrice> for TokenPriceView {
+    type Error = eyre::Report;
+
+    fn try_from(price: &TokenPrice) -> Result<Self, Self::Error> {
+        let denom = Denom::new(price.denom.clone())?;
+        let invalid = |err: String| eyre!("invalid token price for {denom}: {err}");
+        let exponent = u8::try_from(price.exponent)
+            .ok()
+            .filter(|e| *e as u32 <= Dec::PRECISION)
+            .ok_or_else(|| invalid(format!("exponent {} out of range", price.exponent)))?;
+        let usd_price =
+            parse_field(&price.usd_price, "usd_price").map_err(|err| invalid(err.to_string()))?;
+        if usd_price.is_negative() {
+            return Err(invalid(format!("negative usd_price {usd_price}")));
+        }
+
+        Ok(Self {
+            denom,
+            usd_price,
+            exponent,
+            last_updated_block: price.last_updated_block,
+        })
+    }
+}
+
+/// A token price along with how old it was at a given height
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenPriceQuote {
+    pub price: TokenPriceView,
+    /// The height the age was measured at
+    pub height: u64,
+    pub age_in_blocks: u64,
+    /// Whether the price is older than the auction module's `price_max_block_age`
+    pub is_stale: bool,
+}
+
+impl TokenPriceQuote {
+    /// The USD value of `amount`, failing if the price is stale
+    pub fn usd_value(&self, amount: &TokenAmount) -> Result<Dec> {
+        if self.is_stale {
+            return Err(eyre!(
+                "price of {} is stale: last updated {} blocks ago",
+                self.price.denom,
+                self.age_in_blocks
+            ));
+        }
+
+        self.price.usd_value(amount)
+    }
+}
+
+fn required_coin(coin: &Option<Coin>, field: &str) -> Result<TokenAmount> {
+    let coin = coin.as_ref().ok_or_else(|| eyre!("missing {field}"))?;
+
+    TokenAmount::try_from(coin).map_err(|err| eyre!("invalid {field}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, bid, coin, recorded_bid};
+
+    fn auction() -> Auction {
+        fixtures::auction(1000, 400)
+    }
+
+    #[test]
+    fn test_auction_view() {
+        let view = AuctionView::try_from(auction()).unwrap();
+
+        assert!(view.is_active());
+        assert_eq!(&Denom::USDC, view.fee_token());
+        assert_eq!(600, view.tokens_sold().raw);
+        assert_eq!(
+            "1.5".parse::<Dec>().unwrap(),
+            view.current_unit_price_in_usomm
+        );
+
+        let ended = AuctionView::try_from(Auction {
+            end_block: 200,
+            ..auction()
+        })
+        .unwrap();
+        assert_eq!(AuctionStatus::Ended, ended.status);
+        assert_eq!(Some(200), ended.end_block);
+
+        let json = serde_json::to_string(&view).unwrap();
+        assert_eq!(view, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_auction() {
+        assert!(AuctionView::try_from(Auction {
+            starting_tokens_for_sale: None,
+            ..auction()
+        })
+        .is_err());
+        assert!(AuctionView::try_from(Auction {
+            remaining_tokens_for_sale: coin(400, &Denom::WETH),
+            ..auction()
+        })
+        .is_err());
+        assert!(AuctionView::try_from(Auction {
+            remaining_tokens_for_sale: coin(4000, &Denom::USDC),
+            ..auction()
+        })
+        .is_err());
+        assert!(AuctionView::try_from(Auction {
+            current_unit_price_in_usomm: "1.5.0".to_string(),
+            ..auction()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_bid_view() {
+        let placed = bid(1500, 1000);
+        let recorded = recorded_bid(&placed, 1000, "1500000000000000000", 1500);
+        let view = BidView::try_from(&recorded).unwrap();
+
+        assert_eq!(&Denom::USDC, view.fee_token());
+        assert_eq!(TokenAmount::usomm(1500), view.total_usomm_paid);
+
+        assert!(view.is_for(&placed, "somm1abc"));
+        assert!(!view.is_for(&placed, "somm1def"));
+        assert!(!view.is_for(
+            &Bid {
+                auction_id: 8,
+                ..placed.clone()
+            },
+            "somm1abc"
+        ));
+        assert!(!view.is_for(
+            &Bid {
+                maximum_usomm_in: TokenAmount::usomm(1501),
+                ..placed
+            },
+            "somm1abc"
+        ));
+
+        assert!(BidView::try_from(ProtoBid {
+            max_bid_in_usomm: coin(1500, &Denom::USDC),
+            ..recorded
+        })
+        .is_err());
+    }
+
+    fn token_price() -> TokenPrice {
+        fixtures::token_price(&Denom::WETH, 18, "2000.5", 100)
+    }
+
+    #[test]
+    fn test_token_price_view() {
+        let price = TokenPriceView::try_from(token_price()).unwrap();
+        let amount = TokenAmount::new(Denom::WETH, 500_000_000_000_000_000);
+
+        assert_eq!(18, price.exponent);
+        assert_eq!(
+            "1000.25".parse::<Dec>().unwrap(),
+            price.usd_value(&amount).unwrap()
+        );
+        assert!(price.usd_value(&TokenAmount::usomm(1)).is_err());
+
+        assert_eq!(50, price.age_at(150));
+        assert!(!price.is_stale_at(150, 50));
+        assert!(price.is_stale_at(151, 50));
+
+        let usomm = TokenPriceView {
+            denom: Denom::USOMM,
+            usd_price: "0.05".parse().unwrap(),
+            exponent: 6,
+            last_updated_block: 100,
+        };
+        // 40010 SOMM per WETH is 0.00000004001 usomm per wei
+        assert_eq!(
+            Some("0.00000004001".parse::<Dec>().unwrap()),
+            price.unit_price_in(&usomm)
+        );
+
+        let quote = price.clone().quote_at(151, 50);
+        assert_eq!(51, quote.age_in_blocks);
+        assert!(quote.usd_value(&amount).is_err());
+        assert!(price.quote_at(120, 50).usd_value(&amount).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_token_price() {
+        assert!(TokenPriceView::try_from(TokenPrice {
+            exponent: 300,
+            ..token_price()
+        })
+        .is_err());
+        assert!(TokenPriceView::try_from(TokenPrice {
+            usd_price: "-1.0".to_string(),
+            ..token_price()
+        })
+        .is_err());
+    }
+}
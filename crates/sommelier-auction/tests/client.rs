@@ -1,6 +1,14 @@
 use assay::assay;
 use futures::executor::block_on;
-use sommelier_auction::{denom::Denom, AccountInfo};
+use sommelier_auction::{
+    amount::TokenAmount,
+    bid::Bid,
+    dec::{Dec, TokenPriceDecExt},
+    denom::Denom,
+    simulation::simulate_bid_at_price,
+    view::{AuctionStatus, AuctionView, BidView},
+    AccountInfo,
+};
 
 /// Basic no-error or expected error tests for all client queries
 #[assay]
@@ -48,6 +56,59 @@ async fn test_auction_bid_no_error() {
         .contains("No bid found for specified bid id: 2000, and auction id: 1000"));
 }
 
+/// Replays the bids recorded on chain for recently ended auctions through the simulator, each
+/// against the supply left by the bids before it and at the price it was filled at
+#[assay]
+async fn test_simulation_matches_recorded_bids() {
+    let mut client = sommelier_auction::get_default_client().await.unwrap();
+    let mut auctions = block_on(client.ended_auctions()).unwrap();
+    auctions.sort_by_key(|a| std::cmp::Reverse(a.id));
+
+    let mut checked = 0;
+    for auction in auctions.into_iter().take(10) {
+        let ended = AuctionView::try_from(&auction).unwrap();
+        let mut bids: Vec<BidView> = block_on(client.auction_bids(ended.id))
+            .unwrap()
+            .iter()
+            .map(|b| BidView::try_from(b).unwrap())
+            .collect();
+        bids.sort_by_key(|b| b.id);
+
+        let mut remaining = ended.starting_tokens_for_sale.clone();
+        for recorded in bids {
+            let view = AuctionView {
+                status: AuctionStatus::Active,
+                end_block: None,
+                remaining_tokens_for_sale: remaining.clone(),
+                ..ended.clone()
+            };
+            let bid = Bid {
+                auction_id: recorded.auction_id,
+                fee_token: recorded.fee_token().clone(),
+                maximum_usomm_in: recorded.max_bid_in_usomm.clone(),
+                minimum_tokens_out: recorded.sale_token_minimum_amount.clone(),
+            };
+            let fill = simulate_bid_at_price(&view, &bid, recorded.sale_token_unit_price_in_usomm);
+
+            let fill = fill.fill().unwrap_or_else(|| {
+                panic!(
+                    "bid {} of auction {} was rejected: {fill:?}",
+                    recorded.id, view.id
+                )
+            });
+            assert!(
+                fill.matches(&recorded),
+                "simulated {fill:?}, recorded {recorded:?}"
+            );
+
+            remaining = fill.remaining_tokens_for_sale.clone();
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0, "no recorded bids found to check");
+}
+
 #[assay]
 async fn test_token_prices() {
     let mut client = sommelier_auction::get_default_client().await.unwrap();
@@ -63,7 +124,27 @@ async fn test_token_price() {
     let result = block_on(client.token_price(Denom::USDC));
 
     assert!(result.is_ok());
-    assert_eq!(1.0f64, result.unwrap().usd_price.parse::<f64>().unwrap());
+    assert_eq!(Dec::ONE, result.unwrap().usd_price_dec().unwrap());
+}
+
+#[assay]
+async fn test_token_price_quote() {
+    let mut client = sommelier_auction::get_default_client().await.unwrap();
+    let quote = block_on(client.token_price_quote(Denom::USDC)).unwrap();
+
+    assert_eq!(Denom::USDC, quote.price.denom);
+    assert_eq!(6, quote.price.exponent);
+}
+
+#[assay]
+async fn test_upcoming_auctions() {
+    let mut client = sommelier_auction::get_default_client().await.unwrap();
+    let upcoming = block_on(client.upcoming_auctions()).unwrap();
+
+    for auction in upcoming {
+        assert!(!auction.denom.is_usomm());
+        assert_eq!(auction.qualifies, auction.expected_start_block.is_some());
+    }
 }
 
 #[assay]
@@ -73,11 +154,48 @@ async fn test_submit_bid() {
     let bid = sommelier_auction::bid::Bid {
         auction_id: 1000,
         fee_token: Denom::USDC,
-        maximum_usomm_in: 10_000_000_000,
-        minimum_tokens_out: 1_600_000_000,
+        maximum_usomm_in: TokenAmount::usomm(10_000_000_000),
+        minimum_tokens_out: TokenAmount::new(Denom::USDC, 1_600_000_000),
     };
 
     let result = block_on(client.submit_bid(&sender, bid));
 
     println!("{:?}", result);
 }
+
+#[assay]
+async fn test_node_info() {
+    let mut client = sommelier_auction::get_default_client().await.unwrap();
+    let result = block_on(client.node_info());
+
+    assert!(result.is_ok());
+
+    let info = result.unwrap();
+    assert_eq!("sommelier-3", info.network);
+    assert!(info.major_version().is_some());
+}
+
+#[assay]
+async fn test_denom_metadata() {
+    let mut client = sommelier_auction::get_default_client().await.unwrap();
+    let result = block_on(client.denom_metadata(&Denom::USOMM));
+
+    assert!(result.is_ok());
+    assert_eq!(Some(6), result.unwrap().decimals());
+}
+
+#[assay]
+async fn test_denom_to_erc20() {
+    let mut client = sommelier_auction::get_default_client().await.unwrap();
+    let result = block_on(client.denom_to_erc20(&Denom::USDC));
+
+    assert!(result.is_ok());
+
+    let mapping = result.unwrap();
+    assert_eq!(Denom::USDC.erc20_address().unwrap(), mapping.erc20);
+
+    let result = block_on(client.erc20_to_denom(&mapping.erc20));
+
+    assert!(result.is_ok());
+    assert_eq!(Denom::USDC, result.unwrap().denom);
+}
@@ -0,0 +1,104 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+use assay::assay;
+use sommelier_auction::{
+    signer::{
+        BidSigner, RemoteSigner, RemoteSignerEndpoint, RemoteSignerRequest, RemoteSignerResponse,
+    },
+    AccountInfo,
+};
+
+const MNEMONIC: &str = "hockey excess evoke remain render silver buffalo elephant install abandon stuff margin sponsor hero wear rigid glad ancient deputy all snake ginger brother nut";
+
+/// Stand-in for an external signer. Serves `connections` requests on a local TCP port, signing
+/// with a key derived from [`MNEMONIC`], and returns the address it is listening on.
+fn spawn_stand_in_signer(connections: usize, fail: bool) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    thread::spawn(move || {
+        let account = AccountInfo::from_mnemonic(MNEMONIC, "").unwrap();
+        for stream in listener.incoming().take(connections) {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+
+            let request: RemoteSignerRequest = serde_json::from_str(&line).unwrap();
+            let public_key = Some(hex::encode(account.public_key().to_bytes()));
+            let response = match request {
+                _ if fail => RemoteSignerResponse {
+                    error: Some("key is locked".to_string()),
+                    ..Default::default()
+                },
+                RemoteSignerRequest::PublicKey => RemoteSignerResponse {
+                    public_key,
+                    ..Default::default()
+                },
+                RemoteSignerRequest::Sign { sign_doc } => {
+                    let sign_doc = hex::decode(sign_doc).unwrap();
+                    let signature = account.private_key().sign(&sign_doc).unwrap();
+                    RemoteSignerResponse {
+                        public_key,
+                        signature: Some(hex::encode(signature.as_ref())),
+                        ..Default::default()
+                    }
+                }
+            };
+
+            let mut response = serde_json::to_string(&response).unwrap();
+            response.push('\n');
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    addr
+}
+
+#[assay]
+async fn test_remote_signer_matches_local_signer() {
+    let local = AccountInfo::from_mnemonic(MNEMONIC, "").unwrap();
+    let addr = spawn_stand_in_signer(2, false);
+    let remote = RemoteSigner::new(format!("tcp://{addr}").parse().unwrap());
+
+    let sign_doc = b"not actually a sign doc".to_vec();
+    let expected = BidSigner::sign(&local, &sign_doc).await.unwrap();
+
+    assert_eq!(
+        local.address("somm").unwrap(),
+        remote.address("somm").await.unwrap()
+    );
+
+    let output = remote.sign(&sign_doc).await.unwrap();
+    assert_eq!(expected.signature, output.signature);
+    assert_eq!(expected.public_key, output.public_key);
+}
+
+#[assay]
+async fn test_remote_signer_error() {
+    let addr = spawn_stand_in_signer(1, true);
+    let remote = RemoteSigner::new(RemoteSignerEndpoint::Tcp(addr));
+
+    let result = remote.sign(b"sign doc").await;
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("key is locked"));
+}
+
+#[assay]
+async fn test_remote_signer_endpoint_parsing() {
+    assert_eq!(
+        RemoteSignerEndpoint::Tcp("127.0.0.1:9500".to_string()),
+        "tcp://127.0.0.1:9500".parse().unwrap()
+    );
+    assert_eq!(
+        RemoteSignerEndpoint::Unix("/tmp/signer.sock".into()),
+        "unix:///tmp/signer.sock".parse().unwrap()
+    );
+    assert!("http://127.0.0.1:9500"
+        .parse::<RemoteSignerEndpoint>()
+        .is_err());
+}